@@ -6,39 +6,127 @@
 #![allow(clippy::cast_possible_wrap)]
 #![allow(clippy::cast_sign_loss)] // it should only be positive anyway
 
-use chrono::{DateTime, Local, SubsecRound, Timelike};
-use clap::Parser;
+use anyhow::anyhow;
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, Locale, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    SubsecRound, TimeZone, Timelike,
+};
+use chrono_tz::Tz;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use libpt::cli::args::HELP_TEMPLATE;
 use libpt::cli::clap::ArgGroup;
 use libpt::cli::{args::VerbosityLevel, clap};
-use libpt::log::{debug, error, trace};
+use libpt::log::{debug, error, trace, warn};
 use ratatui::backend::CrosstermBackend;
-use ratatui::crossterm::event::{self, poll, Event, KeyCode, KeyModifiers};
-use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use ratatui::style::{Style, Stylize};
-use ratatui::widgets::{Block, Padding, Paragraph};
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{self, poll, Event, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+use ratatui::style::Color;
+use ratatui::widgets::Widget;
 use ratatui::Terminal;
 use std::collections::HashMap;
-use std::io::{Cursor, Stdout, Write};
+use std::fmt::Write as _;
+use std::io::{Stdout, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
+use tui_big_text::PixelSize;
 
+pub mod astro;
+#[cfg(feature = "sound")]
+pub mod audio;
+pub mod chess;
+pub mod config;
+pub mod history;
+pub mod intervals;
+pub mod json;
+pub mod keys;
+pub mod ntp;
+pub mod ringbuffer;
+#[cfg(feature = "sound")]
+pub mod sound;
+pub mod state;
+#[cfg(feature = "sound")]
+pub mod tick;
 pub mod timebar;
+pub mod timesource;
 pub mod ui;
-use timebar::TimeBarLength;
-use ui::Data;
+use config::ConfigFile;
+use history::{HistoryFormat, Record};
+use keys::{parse_key, Action, Key, KeyMap};
+use state::State;
+use timebar::{TimeBar, TimeBarLength};
+use timesource::{SystemTime, TimeSource};
+use ui::{BarStyle, Data, Font, Palette};
+
+/// The options for the timebar, only one of them can be used
+const TIMEBAR_ARGS: &[&str] = &[
+    "minute",
+    "day",
+    "hour",
+    "week",
+    "month",
+    "year",
+    "custom",
+    "countdown",
+    "timer",
+    "pomodoro",
+    "alarm",
+    "until",
+    "since",
+    "new_year",
+    "chess",
+    "intervals",
+];
+
+/// How often to save the state of the running timebar, besides when quitting
+const SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often to redraw the timer with milliseconds and the smooth timebar, about 30 times per
+/// second
+const MS_TICK_RATE: std::time::Duration = std::time::Duration::from_millis(33);
+
+/// How many rounds of the timebars that go on forever the [dry run](ClockConfig::dry_run) shows
+const SCHEDULE_ROUNDS: i64 = 4;
+
+/// How long the [blinking](Clock::timebar_blinks) timebar is shown and then dimmed
+const BLINK_RATE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// How many frames the [flash](Clock::flash) lasts, every other one is inverted
+const FLASH_FRAMES: u8 = 6;
+/// How long each frame of the [flash](Clock::flash) is shown
+const FLASH_RATE: std::time::Duration = std::time::Duration::from_millis(150);
+/// How often the bell rings at the [new year](Clock::new_year), and how long between the rings
+const NEW_YEAR_BELLS: (u32, std::time::Duration) = (5, std::time::Duration::from_millis(300));
+
+/// How many lines the [sparkline](Clock::sparkline) is high
+const SPARKLINE_HEIGHT: u16 = 2;
 
 /// Make your terminal into a big clock
 #[derive(Parser, Debug, Clone)]
 #[command(help_template = HELP_TEMPLATE, author, version)]
-#[clap(group( ArgGroup::new("timebarlen") .args(&["minute","day", "hour", "custom", "countdown", "timer"]),))]
+#[clap(group( ArgGroup::new("timebarlen") .args(TIMEBAR_ARGS),))]
+#[clap(group( ArgGroup::new("zones") .args(&["timezone", "world"]),))]
 #[allow(clippy::struct_excessive_bools)] // the struct is for cli parsing and we already use an
                                          // ArgGroup
-pub struct Clock {
+pub struct ClockConfig {
     #[command(flatten)]
     pub verbose: VerbosityLevel,
     /// Show time since start
     #[clap(short, long)]
     pub timer: bool,
+    /// Start the timer stopped, like a stopwatch
+    ///
+    /// The pause key starts, stops and continues it, the time only counts while it runs.
+    #[clap(long, requires = "timer", conflicts_with = "since")]
+    pub stopped: bool,
+    /// Show the time of the timer with milliseconds, this redraws much more often
+    #[clap(long, requires = "timer")]
+    pub ms: bool,
+    /// Print the laps of the timer when quitting
+    #[clap(long, requires = "timer")]
+    pub print_laps: bool,
 
     // timebar options
     /// show a time bar that tracks progress of the minute
@@ -50,353 +138,2242 @@ pub struct Clock {
     /// show a time bar that tracks progress of the hour
     #[clap(short = 'o', long)]
     pub hour: bool,
+    /// show a time bar that tracks progress of the week, starting on monday
+    #[clap(long)]
+    pub week: bool,
+    /// show a time bar that tracks progress of the month
+    #[clap(long)]
+    pub month: bool,
+    /// show a time bar that tracks progress of the year
+    #[clap(long)]
+    pub year: bool,
     /// show a time bar that tracks progress of a custom duration
     ///
-    /// Precision: only to seconds
-    #[clap(short, long, value_parser = humantime::parse_duration)]
+    /// Like "1h30m", or on a clock like "90:00" for MM:SS or "1:30:00" for HH:MM:SS. It can be
+    /// shorter than a second, like "1500ms", but the timebar only moves once per second unless
+    /// it is a --smooth-bar, and never more often than the --tick-rate.
+    #[clap(short, long, value_parser = parse_timebar_duration)]
     pub custom: Option<std::time::Duration>,
     /// show a time bar that tracks progress of a custom duration without resetting
     ///
+    /// Like "1h30m", or on a clock like "90:00" for MM:SS or "1:30:00" for HH:MM:SS.
     /// Precision: only to seconds
-    #[clap(short = 'u', long, value_parser = humantime::parse_duration)]
+    #[clap(short = 'u', long, value_parser = parse_timebar_duration)]
     pub countdown: Option<std::time::Duration>,
+    /// show a time bar that counts up to an alarm at this time of the day, like "14:30"
+    ///
+    /// If the time has already passed today, the alarm is for tomorrow. Can be given multiple
+    /// times, the alarms then go off one after another.
+    #[clap(long, value_parser = parse_alarm)]
+    pub alarm: Vec<NaiveTime>,
+    /// show a time bar that counts down to a date and time, like "2025-12-25T00:00:00"
+    ///
+    /// The time can be left out to count down to midnight of that date.
+    #[clap(long, value_parser = parse_until)]
+    pub until: Option<DateTime<Local>>,
+    /// Show the time since a date and time in the past, like "2024-01-01T00:00:00"
+    ///
+    /// This is the timer, but started back then instead of now.
+    #[clap(long, value_parser = parse_until)]
+    pub since: Option<DateTime<Local>>,
+    /// Count down to the next new year, and celebrate it with some bells and a flash
+    #[clap(long)]
+    pub new_year: bool,
+    /// Play a chess clock, two countdowns of this duration that take turns, like "5m"
+    ///
+    /// The first press of space or an arrow key starts the clock of player 1, each press after
+    /// that ends the turn and starts the clock of the other player.
+    #[clap(long, value_parser = parse_timebar_duration, conflicts_with = "json")]
+    pub chess: Option<std::time::Duration>,
+    /// Show how much time is left instead of how much has passed
+    #[clap(long)]
+    pub show_remaining: bool,
+    /// Show the time left on the timebar in the big digits, like a kitchen timer
+    ///
+    /// The time of day moves next to the date. The timer shows how long it has been running.
+    #[clap(
+        long,
+        requires = "timebarlen",
+        conflicts_with_all = ["ms", "binary", "chess", "bar_only", "json"]
+    )]
+    pub big_countdown: bool,
+    /// Round the time in the label of the timebar, like "about 1 hour" or "2 hours"
+    #[clap(long)]
+    pub fuzzy: bool,
+    /// Turn the timebar red and blink when less than this time is left, like "10s"
+    #[clap(long, value_parser = humantime::parse_duration)]
+    pub warn_threshold: Option<std::time::Duration>,
+    /// Show how full the timebar is in percent in its label
+    #[clap(long)]
+    pub percent: bool,
+    /// Keep counting how long the countdown or alarm is over once it is up
+    #[clap(long)]
+    pub overtime: bool,
+    /// Start the countdown again each time it is up
+    #[clap(long, requires = "countdown")]
+    pub repeat: bool,
+    /// Beep once per second in the last seconds of the countdown, like "3s"
+    #[clap(long, value_parser = humantime::parse_duration)]
+    pub prewarn: Option<std::time::Duration>,
+    /// How long to snooze the countdown or alarm when pressing z after it went off
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "5m")]
+    pub snooze: std::time::Duration,
+    /// Alternate between working and taking a break, with a notification after each phase
+    #[clap(long)]
+    pub pomodoro: bool,
+    /// Length of the work phase of the pomodoro timer
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "25m", requires = "pomodoro")]
+    pub pomodoro_work: std::time::Duration,
+    /// Length of the break phase of the pomodoro timer
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "5m", requires = "pomodoro")]
+    pub pomodoro_break: std::time::Duration,
+    /// Count down named steps one after another, like "work:40s,rest:20s x8" for 8 rounds
+    ///
+    /// There is a notification after each step, so you know when to switch.
+    #[clap(long)]
+    pub intervals: Option<intervals::Intervals>,
+    /// Show the time in the 12-hour format with AM/PM
+    #[clap(short = 'T', long)]
+    pub twelve_hour: bool,
+    /// Use a custom format for the time, see the strftime documentation of chrono
+    ///
+    /// Defaults to "%H:%M:%S"
+    #[clap(long, conflicts_with = "twelve_hour")]
+    pub time_format: Option<String>,
+    /// Use a custom format for the date, see the strftime documentation of chrono
+    ///
+    /// Defaults to "%Y-%m-%d"
+    #[clap(long)]
+    pub date_format: Option<String>,
+    /// Add the ISO week and the day of the year to the date, like "2024-06-10 (W24, day 162)"
+    #[clap(long)]
+    pub extended_date: bool,
+    /// Write the names of weekdays and months in this language, like `de_DE`
+    ///
+    /// Only makes a difference with a date or time format that has names, like "%A %B".
+    #[clap(long, value_parser = parse_locale)]
+    pub locale: Option<Locale>,
+    /// Show the time in this timezone instead of the local one, like "Asia/Tokyo"
+    #[clap(long = "tz", value_parser = parse_timezone)]
+    pub timezone: Option<Tz>,
+    /// Show a world clock with the time in each of these timezones, like "UTC,Asia/Tokyo"
+    ///
+    /// Timebars are not available in this mode.
+    #[clap(
+        long,
+        value_delimiter = ',',
+        value_parser = parse_timezone,
+        conflicts_with = "timebarlen"
+    )]
+    pub world: Vec<Tz>,
+    /// Continue the countdown, timer, custom or pomodoro timebar that ran when crock was quit
+    #[clap(long, conflicts_with_all = ["timebarlen", "world"])]
+    pub resume: bool,
+    /// Print a single frame of the clock as text and exit, for screenshots
+    #[clap(long, conflicts_with = "json")]
+    pub once: bool,
+    /// Print when the countdown, alarms, intervals or pomodoro phases will go off and exit
+    #[clap(long, conflicts_with_all = ["json", "once"])]
+    pub dry_run: bool,
+    /// Show the minute, hour and day timebars stacked, all at once
+    #[clap(long, conflicts_with_all = ["timebarlen", "world"])]
+    pub all_bars: bool,
+    /// Show how full the timebar was over the last minutes below it
+    #[clap(long, conflicts_with_all = ["all_bars", "world"])]
+    pub sparkline: bool,
+    /// Leave out the border and its titles, for more room for the clock
+    #[clap(long)]
+    pub no_border: bool,
+    /// Empty cells on each side of the clock [default: a 16th of the terminal]
+    ///
+    /// Never more than a quarter of the terminal on each side.
+    #[clap(long)]
+    pub padding: Option<u16>,
+    /// Title on top of the border, like "Kitchen Timer" [default: crock]
+    #[clap(long, conflicts_with = "no_border")]
+    pub title: Option<String>,
+    /// Show the time or what is left of the countdown in the title of the terminal window
+    ///
+    /// The old title comes back when crock quits, if the terminal remembers it.
+    #[clap(long, conflicts_with = "json")]
+    pub set_title: bool,
+    /// Leave out the version at the bottom of the border
+    #[clap(long)]
+    pub no_version: bool,
+    /// Show the time in UTC in a small line below the clock
+    #[clap(long, conflicts_with = "world")]
+    pub with_utc: bool,
+    /// Show the phase of the moon in the bottom right corner
+    #[clap(long, conflicts_with = "world")]
+    pub moon: bool,
+    /// Show the timezone and its offset from UTC in the bottom left corner
+    ///
+    /// This is always shown with `--tz` or `--with-utc`.
+    #[clap(long, conflicts_with = "world")]
+    pub show_tz: bool,
+    /// Correct the time with the time of this NTP server, like "pool.ntp.org" or "host:port"
+    ///
+    /// The server is asked again every 15 minutes. If it can't be reached, the local time is
+    /// used.
+    #[clap(long)]
+    pub ntp: Option<String>,
+    /// Pretend that it is this time when crock starts, like "2024-06-10T14:59:30"
+    ///
+    /// The time goes on from there, for recordings and tests that should look the same every
+    /// time.
+    #[clap(long, hide = true, value_parser = parse_until, conflicts_with = "ntp")]
+    pub mock_now: Option<DateTime<Local>>,
+    /// Print the time as one line of JSON per second instead of showing the TUI
+    #[clap(long, conflicts_with = "world")]
+    pub json: bool,
+    /// Show the time as a binary clock, with a column of dots for each digit
+    #[clap(long, conflicts_with = "world")]
+    pub binary: bool,
+    /// The size of the big clock, it is picked to fit the terminal if not given
+    #[clap(long, value_enum, conflicts_with = "binary")]
+    pub font: Option<Font>,
+    /// Only use ASCII characters, for terminals that can't show the block characters
+    #[clap(long, conflicts_with_all = ["binary", "font", "sparkline"])]
+    pub ascii: bool,
+    /// Blink the colons between the digits of the clock once per second
+    #[clap(long)]
+    pub blink: bool,
+    /// Show the hour without a leading zero on the big clock, like "9:05:03"
+    #[clap(long)]
+    pub no_leading_zero: bool,
+    /// Show the weekday, date and time in one small line instead of the big clock
+    ///
+    /// This leaves more room for the timebars, like in a small tmux pane.
+    #[clap(long, conflicts_with_all = ["binary", "font", "world", "chess"])]
+    pub compact: bool,
+    /// Show only the timebar and its label, without the clock and the date
+    ///
+    /// The timebar gets the whole width, like for a status bar in a small pane.
+    #[clap(long, conflicts_with_all = ["binary", "font", "world", "chess", "compact", "with_utc"])]
+    pub bar_only: bool,
+    /// Show when the timebar will be full below the date, like "done at 15:42:00"
+    #[clap(long)]
+    pub show_eta: bool,
+    /// How the timebar is drawn
+    #[clap(long, value_enum, default_value_t = BarStyle::Line)]
+    pub bar_style: BarStyle,
+    /// Empty the timebar as time passes instead of filling it, like an hourglass
+    #[clap(long)]
+    pub drain: bool,
+    /// Move the timebar between the seconds instead of once per second
+    ///
+    /// This redraws about 30 times per second instead of once, so it takes noticeably more CPU.
+    #[clap(long)]
+    pub smooth_bar: bool,
+    /// Shift when the minute, hour or day timebar starts over, like "30s"
+    ///
+    /// With "30s", the minute timebar is empty at :30 of each minute instead of at :00. It must
+    /// be shorter than the timebar.
+    #[clap(long, value_parser = parse_timebar_duration)]
+    pub phase: Option<std::time::Duration>,
+    /// Color the timebar from green to red as it fills up
+    #[clap(long)]
+    pub gradient: bool,
+    /// The longest time to sleep before checking the time again
+    ///
+    /// The clock already wakes up whenever the shown time changes or a key is pressed, so this
+    /// is rarely needed.
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "1s")]
+    pub tick_rate: std::time::Duration,
+    /// Color of the clock digits and the timebar, either a name like "green" or hex like "#ff8800"
+    #[clap(long)]
+    pub color: Option<String>,
+    /// The colors of everything, some are made to be easier to tell apart
+    ///
+    /// `mono` uses no colors at all and says in the label when the timebar is done.
+    #[clap(long, value_enum, default_value_t = Palette::Default)]
+    pub palette: Palette,
+    /// Leave out all colors, like the mono palette
+    ///
+    /// This is also done if the `NO_COLOR` environment variable is set, or `TERM` is "dumb".
+    #[clap(long, conflicts_with_all = ["color", "palette"])]
+    pub no_color: bool,
+    /// Quit once the countdown or the last alarm is up
+    #[clap(long)]
+    pub exit_on_finish: bool,
+    /// How long to keep showing the finished countdown before quitting
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "3s", requires = "exit_on_finish")]
+    pub exit_delay: std::time::Duration,
+    /// Run this shell command when the countdown is up, like "notify-send done"
+    #[clap(long)]
+    pub exec: Option<String>,
+    /// Also notify when the timebar is this many percent full, like "25,50,75"
+    #[clap(long, value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..100))]
+    pub notify_at: Vec<u8>,
+    /// Add each finished countdown and the timer to ~/.local/share/crock/history.log
+    ///
+    /// Each line has when it started, how long it was and the title or what kind of timebar it
+    /// was. The timer is logged when crock quits.
+    #[clap(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "jsonl")]
+    pub log_history: Option<HistoryFormat>,
+    /// Click on the timebar to restart it and on the clock to pause it
+    #[clap(long)]
+    pub mouse: bool,
+    /// Keys to quit, like "q,esc" [default: q, esc, ctrl-c]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_quit: Vec<Key>,
+    /// Keys to show or hide the seconds [default: s]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_seconds: Vec<Key>,
+    /// Keys to start a new lap of the timer [default: l]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_lap: Vec<Key>,
+    /// Keys to snooze the countdown or alarm [default: z]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_snooze: Vec<Key>,
+    /// Keys to pause or resume the timebar [default: space]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_pause: Vec<Key>,
+    /// Keys to restart the timebar [default: r]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_reset: Vec<Key>,
+    /// Keys to end the turn in the chess clock [default: left, right]
+    #[clap(long, value_delimiter = ',', value_parser = parse_key)]
+    pub key_switch: Vec<Key>,
+    /// Beep every time a new hour starts, like a grandfather clock
+    #[clap(long)]
+    pub chime: bool,
+    /// Beep as many times as the hour on the 12-hour clock, once per second
+    #[clap(long, requires = "chime")]
+    pub chime_count: bool,
+    /// Flash the screen when the countdown is up, for when the sound is off
+    #[clap(long)]
+    pub flash: bool,
+    /// Don't ring the terminal bell, the sound and desktop notification still go off
+    #[clap(long)]
+    pub no_beep: bool,
     /// Play a notification sound when the countdown is up
     #[cfg(feature = "sound")]
     #[clap(short, long, default_value_t = true)]
     pub sound: bool,
+    /// Volume of the notification sound, from 0.0 (muted) to 1.0 (full)
+    #[cfg(feature = "sound")]
+    #[clap(long, default_value_t = 1.0, value_parser = parse_volume)]
+    pub volume: f32,
+    /// Click once every second, like a metronome
+    #[cfg(feature = "sound")]
+    #[clap(long)]
+    pub tick_sound: bool,
+    /// Play the sounds on this audio output instead of the default one
+    ///
+    /// A part of the name is enough, see --list-audio-devices for the names.
+    #[cfg(feature = "sound")]
+    #[clap(long)]
+    pub audio_device: Option<String>,
+    /// Print the names of the audio outputs and exit
+    #[cfg(feature = "sound")]
+    #[clap(long)]
+    pub list_audio_devices: bool,
+    /// Play this sound file instead of the bundled sounds, like mp3, wav, flac or ogg
+    ///
+    /// The sounds for the single kinds of notifications, like --sound-milestone, still win.
+    #[cfg(feature = "sound")]
+    #[clap(long)]
+    pub sound_file: Option<std::path::PathBuf>,
+    /// The sound when the countdown, alarm or another timebar is up
+    ///
+    /// Either one of the bundled sounds, "alarm" or "notify", or a sound file.
+    #[cfg(feature = "sound")]
+    #[clap(long, value_name = "SOUND")]
+    pub sound_done: Option<sound::Sound>,
+    /// The sound when the work of the pomodoro is done and the break starts [default: alarm]
+    #[cfg(feature = "sound")]
+    #[clap(long, value_name = "SOUND")]
+    pub sound_work_over: Option<sound::Sound>,
+    /// The sound when the break of the pomodoro is over [default: notify]
+    #[cfg(feature = "sound")]
+    #[clap(long, value_name = "SOUND")]
+    pub sound_break_over: Option<sound::Sound>,
+    /// The sound for the milestones of --notify-at [default: notify]
+    #[cfg(feature = "sound")]
+    #[clap(long, value_name = "SOUND")]
+    pub sound_milestone: Option<sound::Sound>,
+    /// How urgent the desktop notification is, critical ones stay until they are closed
+    #[cfg(feature = "desktop")]
+    #[clap(long, value_enum, default_value_t = Urgency::Normal)]
+    pub notify_urgency: Urgency,
+    /// How long the desktop notification is shown, like "10s"
+    ///
+    /// Defaults to whatever the notification server does.
+    #[cfg(feature = "desktop")]
+    #[clap(long, value_parser = humantime::parse_duration)]
+    pub notify_timeout: Option<std::time::Duration>,
+    /// What the desktop notification says, like "Break over!"
+    ///
+    /// "{duration}" is replaced with how long the timebar that is done was.
+    #[cfg(feature = "desktop")]
+    #[clap(long)]
+    pub notify_message: Option<String>,
+}
+
+/// The clock itself, with its [options](ClockConfig) and everything that changes while it runs
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // these are unrelated states
+pub struct Clock {
+    /// the options from the command line and the config file
+    pub config: ClockConfig,
 
     // internal variables
-    #[clap(skip)]
     pub(crate) last_reset: Option<DateTime<Local>>,
-    #[clap(skip)]
     pub(crate) did_notify: bool,
+    pub(crate) paused: bool,
+    pub(crate) paused_at: Option<DateTime<Local>>,
+    pub(crate) paused_total: chrono::Duration,
+    /// how much longer the countdown or alarm is because of snoozing
+    pub(crate) snoozed: chrono::Duration,
+    /// set when we got SIGINT or SIGTERM and should quit
+    pub(crate) terminate: Arc<AtomicBool>,
+    /// how far the local clock is off from the NTP server in milliseconds, updated in the
+    /// background
+    pub(crate) ntp_offset: Arc<AtomicI64>,
+    /// where the current time comes from, see [`now`](Self::now)
+    pub(crate) time_source: Arc<dyn TimeSource>,
+    /// how far the [mocked time](ClockConfig::mock_now) is off from the local clock
+    pub(crate) mock_offset: chrono::Duration,
+    /// how long each lap of the timer took
+    pub(crate) laps: Vec<chrono::Duration>,
+    pub(crate) custom_color: Option<Color>,
+    pub(crate) keymap: KeyMap,
+    pub(crate) show_seconds: bool,
+    pub(crate) repeat_round: u32,
+    pub(crate) alarm_idx: usize,
+    pub(crate) pomodoro_on_break: bool,
+    pub(crate) pomodoro_round: u32,
+    /// the step of the [intervals](ClockConfig::intervals), counting the steps of all rounds
+    pub(crate) interval_idx: usize,
+    /// the hour we last saw, to notice when the next one starts
+    pub(crate) chime_hour: Option<u32>,
+    pub(crate) chimes_left: u32,
+    pub(crate) last_chime: Option<DateTime<Local>>,
+    /// when the timer was started, if it was [resumed](ClockConfig::resume) or started
+    /// [since](ClockConfig::since) some time in the past
+    pub(crate) resumed_started_at: Option<DateTime<Local>>,
+    /// how many frames of the [flash](ClockConfig::flash) are left
+    pub(crate) flash_frames: u8,
+    /// the seconds that were left at the last [prewarn](ClockConfig::prewarn) beep
+    pub(crate) last_prewarn: Option<i64>,
+    /// which percentages of [notify at](ClockConfig::notify_at) were passed, one bit each
+    pub(crate) milestones: u128,
+    /// the last reset of the timebar the milestones are about
+    pub(crate) milestones_reset: Option<DateTime<Local>>,
+    /// what we last put into the [window title](ClockConfig::set_title)
+    pub(crate) window_title: Option<String>,
+    /// both clocks of the [chess clock](ClockConfig::chess)
+    pub(crate) chess: Option<chess::ChessState>,
+    /// plays the [tick sound](ClockConfig::tick_sound)
+    #[cfg(feature = "sound")]
+    pub(crate) ticker: Option<tick::Ticker>,
+    #[cfg(feature = "sound")]
+    pub(crate) last_tick: Option<DateTime<Local>>,
+}
+
+impl ClockConfig {
+    /// Parse the command line arguments and take everything else from the config file
+    ///
+    /// Options given on the command line always win over the config file. Since only one timebar
+    /// can be used, choosing one on the command line ignores all timebars from the config file.
+    /// The same goes for `--tz` and `--world`.
+    ///
+    /// # Errors
+    ///
+    /// The config file could not be read or is invalid. A missing config file is fine.
+    pub fn from_config_and_args() -> anyhow::Result<Self> {
+        let matches = Self::command().get_matches();
+        let mut config = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        if let Some(file) = ConfigFile::load()? {
+            file.apply(&mut config, &matches)?;
+        }
+        Ok(config)
+    }
 }
 
 impl Clock {
+    /// A clock with `config` that has not started yet, see [`setup`](Self::setup)
+    #[must_use]
+    pub fn new(config: ClockConfig) -> Self {
+        let mock_offset = config
+            .mock_now
+            .map_or_else(chrono::Duration::zero, |now| now - Local::now());
+        Self {
+            time_source: Arc::new(SystemTime),
+            mock_offset,
+            config,
+            last_reset: None,
+            did_notify: false,
+            paused: false,
+            paused_at: None,
+            paused_total: chrono::Duration::zero(),
+            snoozed: chrono::Duration::zero(),
+            terminate: Arc::default(),
+            ntp_offset: Arc::default(),
+            laps: Vec::new(),
+            custom_color: None,
+            keymap: KeyMap::default(),
+            show_seconds: true,
+            repeat_round: 0,
+            alarm_idx: 0,
+            pomodoro_on_break: false,
+            pomodoro_round: 0,
+            interval_idx: 0,
+            chime_hour: None,
+            chimes_left: 0,
+            last_chime: None,
+            resumed_started_at: None,
+            flash_frames: 0,
+            last_prewarn: None,
+            milestones: 0,
+            milestones_reset: None,
+            window_title: None,
+            chess: None,
+            #[cfg(feature = "sound")]
+            ticker: None,
+            #[cfg(feature = "sound")]
+            last_tick: None,
+        }
+    }
+
+    /// Which timebar to show, if any
+    ///
+    /// # Panics
+    ///
+    /// The month or year timebar is used beyond the last date chrono knows.
     #[must_use]
     #[allow(clippy::missing_const_for_fn)]
-    fn timebar_len(&self) -> Option<TimeBarLength> {
-        if self.minute {
+    pub fn timebar_len(&self) -> Option<TimeBarLength> {
+        if self.config.minute {
             Some(TimeBarLength::Minute)
-        } else if self.day {
+        } else if self.config.day {
             Some(TimeBarLength::Day)
-        } else if self.hour {
+        } else if self.config.hour {
             Some(TimeBarLength::Hour)
-        } else if self.timer {
+        } else if self.config.week {
+            Some(TimeBarLength::Week)
+        } else if self.config.month {
+            Some(TimeBarLength::Month(self.calendar_secs(|date| {
+                date.checked_add_months(Months::new(1)).unwrap()
+            })))
+        } else if self.config.year {
+            Some(TimeBarLength::Year(self.calendar_secs(|date| {
+                date.with_year(date.year() + 1).unwrap()
+            })))
+        } else if self.config.timer || self.config.since.is_some() {
             Some(TimeBarLength::Timer)
-        } else if self.countdown.is_some() {
+        } else if self.config.pomodoro {
+            Some(TimeBarLength::Pomodoro {
+                work: self.config.pomodoro_work.as_secs() as i64,
+                break_: self.config.pomodoro_break.as_secs() as i64,
+            })
+        } else if let Some(step) = self.interval_step() {
+            Some(TimeBarLength::Interval(step.len.as_secs() as i64))
+        } else if let Some(alarm) = self.config.alarm.get(self.alarm_idx) {
+            Some(TimeBarLength::Alarm(
+                self.alarm_secs(*alarm) + self.snoozed.num_seconds(),
+            ))
+        } else if let Some(until) = self.config.until {
+            let start = self.last_reset.unwrap_or_else(|| self.now());
+            Some(TimeBarLength::Until(
+                until.signed_duration_since(start).num_seconds().max(0),
+            ))
+        } else if let Some(countdown) = self.config.countdown {
             Some(TimeBarLength::Countup(
-                self.countdown.unwrap().as_secs() as i64
+                countdown.as_secs() as i64 + self.snoozed.num_seconds(),
             ))
-        } else if self.custom.is_some() {
-            Some(TimeBarLength::Custom(self.custom.unwrap().as_secs() as i64))
         } else {
-            None
+            self.config.custom.map(|custom| {
+                TimeBarLength::Custom(i64::try_from(custom.as_millis()).unwrap_or(i64::MAX))
+            })
+        }
+    }
+
+    /// Seconds from the start of the timebar until the clock shows `alarm` the next time
+    fn alarm_secs(&self, alarm: NaiveTime) -> i64 {
+        let start = self.in_timezone(self.last_reset.unwrap_or_else(|| self.now()));
+        let mut target = start
+            .date_naive()
+            .and_time(alarm)
+            .and_local_timezone(*start.offset())
+            .single()
+            .expect("tried to use a time that does not exist");
+        if target <= start {
+            // that time has already passed today
+            target += chrono::Duration::days(1);
+        }
+        target.signed_duration_since(start).num_seconds()
+    }
+
+    /// Seconds from the start of the timebar until midnight of the date that `next` gives for
+    /// the date it started on
+    ///
+    /// The months and years are not all the same length, so they are counted on the calendar.
+    fn calendar_secs(&self, next: impl FnOnce(NaiveDate) -> NaiveDate) -> i64 {
+        let start = self.last_reset.unwrap_or_else(|| self.now());
+        self.midnight(next(self.in_timezone(start).date_naive()))
+            .signed_duration_since(start)
+            .num_seconds()
+    }
+
+    /// The start of `date` on the wall clock of the chosen timezone
+    fn midnight(&self, date: NaiveDate) -> DateTime<Local> {
+        let midnight = date.and_time(NaiveTime::MIN);
+        self.config
+            .timezone
+            .map_or_else(
+                || Local.from_local_datetime(&midnight).earliest(),
+                |tz| {
+                    tz.from_local_datetime(&midnight)
+                        .earliest()
+                        .map(|time| time.with_timezone(&Local))
+                },
+            )
+            .expect("tried to use a time that does not exist")
+    }
+
+    /// When the week, month or year that `current_time` is in started
+    ///
+    /// Other timebars are not on the calendar, for those this is the start of the day.
+    pub(crate) fn calendar_start(
+        &self,
+        len: TimeBarLength,
+        current_time: DateTime<Local>,
+    ) -> DateTime<Local> {
+        let date = self.in_timezone(current_time).date_naive();
+        self.midnight(match len {
+            TimeBarLength::Week => {
+                date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            TimeBarLength::Month(_) => date.with_day(1).unwrap(),
+            TimeBarLength::Year(_) => date.with_ordinal(1).unwrap(),
+            _ => date,
+        })
+    }
+
+    /// Order the alarms by when they go off next, those that already passed today come last
+    pub(crate) fn sort_alarms(&mut self, now: NaiveTime) {
+        self.config.alarm.sort_unstable();
+        self.config.alarm.dedup();
+        let passed = self
+            .config
+            .alarm
+            .iter()
+            .take_while(|alarm| **alarm <= now)
+            .count();
+        self.config.alarm.rotate_left(passed);
+        self.alarm_idx = 0;
+    }
+
+    /// The format used for rendering the time with seconds
+    #[must_use]
+    pub fn time_format(&self) -> &str {
+        if let Some(format) = &self.config.time_format {
+            format
+        } else if !self.show_seconds {
+            self.time_format_short()
+        } else if self.config.twelve_hour {
+            ui::TIME_FORMAT_12H
+        } else {
+            ui::TIME_FORMAT
+        }
+    }
+
+    /// The current time, corrected with the [NTP](ClockConfig::ntp) server if we have one
+    ///
+    /// Everything that needs the time should ask here, so that it can be
+    /// [mocked](ClockConfig::mock_now).
+    #[must_use]
+    pub fn now(&self) -> DateTime<Local> {
+        self.time_source.now()
+            + chrono::Duration::milliseconds(self.ntp_offset.load(Ordering::Relaxed))
+            + self.mock_offset
+    }
+
+    /// Take the time from `source` instead of the system clock, for example to test without
+    /// waiting for the time to pass
+    pub fn set_time_source(&mut self, source: Arc<dyn TimeSource>) {
+        self.time_source = source;
+    }
+
+    /// Fresh [`Data`] for the current timebar, with the timer started now or when it was
+    /// [resumed](ClockConfig::resume)
+    pub(crate) fn new_data(&self) -> Data {
+        let mut data = Data::new(self.timebar_len());
+        data.set_started_at(
+            self.resumed_started_at
+                .unwrap_or_else(|| self.now().trunc_subsecs(0)),
+        );
+        data
+    }
+
+    /// Show `time` as it is on the wall clock of the chosen timezone
+    ///
+    /// Without a chosen timezone, the local time is used.
+    #[must_use]
+    pub fn in_timezone(&self, time: DateTime<Local>) -> DateTime<FixedOffset> {
+        self.config.timezone.map_or_else(
+            || time.fixed_offset(),
+            |tz| time.with_timezone(&tz).fixed_offset(),
+        )
+    }
+
+    /// The format used for rendering the date
+    #[must_use]
+    pub fn date_format(&self) -> &str {
+        self.config
+            .date_format
+            .as_deref()
+            .unwrap_or(ui::DATE_FORMAT)
+    }
+
+    /// Format `time` with `format`, in the [locale](ClockConfig::locale) if there is one
+    #[must_use]
+    pub fn localized<T: TimeZone>(&self, time: &DateTime<T>, format: &str) -> String
+    where
+        T::Offset: std::fmt::Display,
+    {
+        self.config.locale.map_or_else(
+            || time.format(format).to_string(),
+            |locale| time.format_localized(format, locale).to_string(),
+        )
+    }
+
+    /// The small date for `wall_time`, as it is shown
+    #[must_use]
+    pub fn fdate(&self, wall_time: DateTime<FixedOffset>) -> String {
+        let mut fdate = self.localized(&wall_time, self.date_format());
+        if self.config.extended_date {
+            let _ = write!(
+                fdate,
+                " (W{}, day {})",
+                wall_time.iso_week().week(),
+                wall_time.ordinal()
+            );
+        }
+        fdate
+    }
+
+    /// The format used for rendering the time without seconds
+    #[must_use]
+    pub const fn time_format_short(&self) -> &'static str {
+        if self.config.twelve_hour {
+            ui::TIME_FORMAT_SHORT_12H
+        } else {
+            ui::TIME_FORMAT_SHORT
+        }
+    }
+
+    /// The format for when the timebar starts and ends, with the seconds if the
+    /// [phase](ClockConfig::phase) is not a whole number of minutes
+    #[must_use]
+    pub fn boundary_format(&self) -> &'static str {
+        if self.phase().num_milliseconds() % 60_000 == 0 {
+            self.time_format_short()
+        } else if self.config.twelve_hour {
+            ui::TIME_FORMAT_12H
+        } else {
+            ui::TIME_FORMAT
+        }
+    }
+
+    /// The running timebar as of `current_time`, if there is one
+    ///
+    /// There is none before the clock was [set up](Self::setup), as it does not know when the
+    /// timebar started yet.
+    #[must_use]
+    pub fn timebar(&self, current_time: DateTime<Local>) -> Option<TimeBar> {
+        Some(TimeBar {
+            length: self.timebar_len()?,
+            last_reset: self.last_reset?,
+            paused: self.paused_duration(current_time),
+            on_break: self.pomodoro_on_break,
+            smooth: self.config.smooth_bar,
+        })
+    }
+
+    /// How full the timebar is at `current_time`, from 0.0 to 1.0, see [`TimeBar::ratio`]
+    ///
+    /// `None` without a [timebar](Self::timebar), also before the [setup](Self::setup).
+    #[must_use]
+    pub fn timebar_ratio(&self, current_time: DateTime<Local>) -> Option<f64> {
+        self.timebar(current_time)
+            .map(|timebar| timebar.ratio(current_time))
+    }
+
+    /// Seconds until the timebar is full at `current_time`, never negative
+    ///
+    /// The [Timer](TimeBarLength::Timer) is never full, so there is nothing remaining.
+    #[must_use]
+    pub fn timebar_remaining(&self, current_time: DateTime<Local>) -> Option<i64> {
+        let len = self.timebar_len()?;
+        if len == TimeBarLength::Timer {
+            return None;
         }
+        let elapsed = current_time.signed_duration_since(self.last_reset?)
+            - self.paused_duration(current_time);
+        Some((len.phase_secs(self.pomodoro_on_break) - elapsed.num_seconds()).max(0))
     }
 
-    #[allow(clippy::cast_precision_loss)] // okay, good to know, but I accept the loss. It
-                                          // shouldn't come to more than 2^52 seconds anyway
-    pub(crate) fn timebar_ratio(&self, current_time: DateTime<Local>) -> Option<f64> {
+    /// When the timebar will be full, as of the time in `data`
+    ///
+    /// The wall clock timebars start on a whole minute, hour or day, so they also end on one. The
+    /// others end later if they were paused. The [Timer](TimeBarLength::Timer) is never full.
+    #[must_use]
+    pub fn timebar_eta(&self, data: &Data) -> Option<DateTime<Local>> {
         let len = self.timebar_len()?;
-        let since = current_time
-            .signed_duration_since(self.last_reset.unwrap())
-            .num_seconds() as f64;
-        #[cfg(debug_assertions)]
-        if since < 1.0 {
-            trace!("ratio calculation since is now <1: {:#?}", since);
+        if len == TimeBarLength::Timer {
+            return None;
         }
-        Some((since / len.as_secs() as f64).clamp(0.0, 1.0))
+        Some(
+            self.last_reset?
+                + self.paused_duration(*data.now())
+                + chrono::Duration::milliseconds(len.phase_millis(self.pomodoro_on_break)),
+        )
     }
 
-    pub(crate) fn maybe_reset_since_zero(&mut self) {
-        if let Some(len) = self.timebar_len() {
-            let since_last_reset = Local::now().signed_duration_since(self.last_reset.unwrap());
+    /// Start the timebar over if it is full, or go on with the next phase, alarm or round
+    ///
+    /// Nothing happens before the clock was [set up](Self::setup).
+    ///
+    /// # Panics
+    ///
+    /// The start of the current minute does not exist in the timezone.
+    pub fn maybe_reset_since_zero(&mut self, current_time: DateTime<Local>) {
+        if let (Some(len), Some(last_reset)) = (self.timebar_len(), self.last_reset) {
+            let since_last_reset = current_time.signed_duration_since(last_reset);
+            // the wall clock modes need to reset at the boundaries of the chosen timezone, shifted
+            // by the phase
+            let wall_now = self.in_timezone(current_time) - self.phase();
+            let start_of_minute = || {
+                wall_now
+                    .trunc_subsecs(0)
+                    .with_second(0)
+                    .expect("tried to use a time that does not exist")
+                    .with_timezone(&Local)
+                    + self.phase()
+            };
             match len {
-                TimeBarLength::Countup(_) | TimeBarLength::Timer => {
-                    // the count up should not reset. If the time is over, just keep it at 100%
+                TimeBarLength::Countup(_) if self.config.repeat => {
+                    // start over once the end was reached and notified about. The next round
+                    // starts where this one should end, so that the rounds don't drift
+                    if self.did_notify {
+                        self.start_next_round(len.as_secs());
+                        self.repeat_round += 1;
+                        debug!("repeating the countdown, round {}", self.repeat_round + 1);
+                    }
                 }
-                TimeBarLength::Custom(_) => {
-                    // BUG: this is not consistent, sometimes leads to wrong seconds
-                    if since_last_reset.num_milliseconds() >= 100
-                        && since_last_reset.num_seconds() >= len.as_secs()
-                    {
-                        self.last_reset = Some(Local::now().round_subsecs(0));
+                TimeBarLength::Alarm(secs) if self.alarm_idx + 1 < self.config.alarm.len() => {
+                    // go on with the next alarm, counting from when this one went off
+                    if self.did_notify {
+                        self.last_reset = Some(last_reset + chrono::Duration::seconds(secs));
+                        self.did_notify = false;
+                        self.alarm_idx += 1;
+                        debug!("next alarm: {}", self.config.alarm[self.alarm_idx]);
                     }
                 }
-                TimeBarLength::Minute => {
-                    if since_last_reset.num_seconds() >= 1 && Local::now().second() == 0 {
-                        self.last_reset = Some(
-                            Local::now()
-                                .round_subsecs(0)
-                                .with_second(1)
-                                .expect("tried to use a time that does not exist"),
-                        );
-                        debug!("reset the time of the time bar (minute)");
+                TimeBarLength::Interval(secs) if self.goes_on() => {
+                    // go on with the next step where this one should end, so they don't drift
+                    if self.did_notify {
+                        self.start_next_round(secs);
+                        self.interval_idx += 1;
+                        debug!("next step of the intervals: {:?}", self.interval_step());
                     }
                 }
-                TimeBarLength::Hour => {
-                    if since_last_reset.num_minutes() >= 1 && Local::now().minute() == 0 {
+                TimeBarLength::Countup(_)
+                | TimeBarLength::Alarm(_)
+                | TimeBarLength::Until(_)
+                | TimeBarLength::Interval(_)
+                | TimeBarLength::Timer => {
+                    // the count up should not reset. If the time is over, just keep it at 100%
+                }
+                TimeBarLength::Custom(millis) => {
+                    let since_last_reset = since_last_reset - self.paused_duration(current_time);
+                    // the next period starts where the last one should end, not when we noticed
+                    // it ended, so that the periods don't drift. Skip the periods we missed.
+                    let periods = since_last_reset
+                        .num_milliseconds()
+                        .checked_div(millis)
+                        .unwrap_or(0);
+                    if periods >= 1 {
                         self.last_reset = Some(
-                            Local::now()
-                                .round_subsecs(0)
-                                .with_second(1)
-                                .expect("tried to use a time that does not exist"),
+                            last_reset
+                                + chrono::Duration::milliseconds(periods * millis)
+                                + self.paused_total,
                         );
-                        debug!("reset the time of the time bar (hour)");
+                        self.paused_total = chrono::Duration::zero();
+                        self.finish();
                     }
                 }
-                TimeBarLength::Day => {
-                    if since_last_reset.num_hours() >= 1 && Local::now().hour() == 0 {
-                        self.last_reset = Some(
-                            Local::now()
-                                .round_subsecs(0)
-                                .with_second(1)
-                                .expect("tried to use a time that does not exist"),
+                TimeBarLength::Pomodoro { .. } => {
+                    let since_last_reset = since_last_reset - self.paused_duration(current_time);
+                    let phase = chrono::Duration::seconds(len.phase_secs(self.pomodoro_on_break));
+                    if since_last_reset >= phase {
+                        // like the custom timebar, so the phases go off when the dry run says
+                        self.last_reset = Some(last_reset + phase + self.paused_total);
+                        self.paused_total = chrono::Duration::zero();
+                        if self.pomodoro_on_break {
+                            self.pomodoro_round += 1;
+                        }
+                        self.pomodoro_on_break = !self.pomodoro_on_break;
+                        debug!(
+                            "pomodoro phase is over, now on break: {}",
+                            self.pomodoro_on_break
                         );
-                        debug!("reset the time of the time bar (day)");
+                        self.finish();
                     }
                 }
-            }
-        }
-    }
-
-    fn setup_last_reset(&mut self) {
-        if let Some(len) = self.timebar_len() {
-            trace!("Local Time: {}", Local::now());
-            match len {
-                TimeBarLength::Custom(_) | TimeBarLength::Countup(_) | TimeBarLength::Timer => {
-                    self.last_reset = Some(Local::now());
-                }
                 TimeBarLength::Minute => {
-                    self.last_reset = Some(
-                        Local::now()
-                            .round_subsecs(0)
-                            .with_second(1)
-                            .expect("tried to use a time that does not exist"),
-                    );
+                    if since_last_reset.num_seconds() >= 1 && wall_now.second() == 0 {
+                        self.last_reset = Some(start_of_minute());
+                        debug!("reset the time of the time bar (minute)");
+                    }
                 }
                 TimeBarLength::Hour => {
-                    self.last_reset = Some(
-                        Local::now()
-                            .round_subsecs(0)
-                            .with_second(1)
-                            .expect("tried to use a time that does not exist")
-                            .with_minute(0)
-                            .expect("tried to use a time that does not exist"),
-                    );
+                    if since_last_reset.num_minutes() >= 1 && wall_now.minute() == 0 {
+                        self.last_reset = Some(start_of_minute());
+                        debug!("reset the time of the time bar (hour)");
+                    }
                 }
                 TimeBarLength::Day => {
-                    self.last_reset = Some(
-                        Local::now()
-                            .round_subsecs(0)
-                            .with_second(1)
-                            .expect("tried to use a time that does not exist")
-                            .with_minute(0)
-                            .expect("tried to use a time that does not exist")
-                            .with_hour(0)
-                            .expect("tried to use a time that does not exist"),
-                    );
+                    if since_last_reset.num_hours() >= 1 && wall_now.hour() == 0 {
+                        self.last_reset = Some(start_of_minute());
+                        debug!("reset the time of the time bar (day)");
+                    }
+                }
+                TimeBarLength::Week | TimeBarLength::Month(_) | TimeBarLength::Year(_) => {
+                    if since_last_reset.num_seconds() >= len.as_secs() {
+                        self.last_reset = Some(self.calendar_start(len, current_time));
+                        debug!("reset the time of the time bar ({len:?})");
+                    }
                 }
             }
-            debug!("set up initial last reset as {:#?}", self.last_reset);
         }
     }
 
-    #[allow(clippy::unnecessary_wraps)] // we have that to be future proof
-    pub(crate) fn setup(&mut self) -> anyhow::Result<()> {
-        self.setup_last_reset();
-        Ok(())
+    /// How long the timebar has been paused in total, including a currently running pause
+    #[must_use]
+    pub fn paused_duration(&self, current_time: DateTime<Local>) -> chrono::Duration {
+        match self.paused_at {
+            Some(paused_at) if self.paused => {
+                self.paused_total + current_time.signed_duration_since(paused_at)
+            }
+            _ => self.paused_total,
+        }
     }
 
-    /// Run the clock TUI
-    ///
-    /// # Errors
+    /// Pause or resume the timebar
     ///
-    /// * The [setup](Self::setup) fails
-    /// * Drawing the [ui](Self::ui) fails
-    /// * Polling or reading an event fails
-    pub(crate) fn run(
-        mut self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    ) -> anyhow::Result<()> {
-        let tick_rate = std::time::Duration::from_millis(100);
-        let mut last_tick = Instant::now();
-        let mut uidata: Data = Data::new(self.timebar_len());
-        self.setup()?;
-        loop {
-            let raw_time = chrono::Local::now().round_subsecs(0);
-            let splits: Vec<String> = raw_time
-                .naive_local()
-                .to_string()
-                .split_whitespace()
-                .map(str::to_string)
-                .collect();
-
-            // We somehow fill timebar_ratio with a bad value here if we don't add 1 second. It's
-            // always the value that would be right for now-1s. The start of the minute is
-            // special, with this strategy it is 100%. #10
-            //
-            // If we manually add a second, it works as expected, but it feels weird. We use the
-            // same time for all of the datapoints here, so it can't be because of time diff in
-            // calculation. I noticed that we don't start at 0% this way (with len=minute)
-            // . Normally, chrono does not include 60 seconds, only letting it range between 0 and
-            // 59. This makes sense but feels weird to the human understanding, of course there are
-            // seconds in a minute! If we do it this way, we don't quite start at 0%, but 100%,
-            // which feels correct.
-            //
-            // In short: if we add a second here, we get the correct percentages. 01:00 is 100%,
-            // 01:30 is 50%, 01:59 is 98%, 01:60 does not exist because that's how counting from
-            // 0 works.
-
-            let now = raw_time + chrono::Duration::seconds(1);
-            uidata.update(
-                now,
-                splits[0].clone(),
-                splits[1].clone(),
-                self.timebar_ratio(now),
-            );
-            if uidata.changed() {
-                self.ui(terminal, &uidata)?;
-            }
-            let timeout = tick_rate.saturating_sub(last_tick.elapsed());
-            if poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
-                    if key.code == KeyCode::Char('q')
-                        || key.code == KeyCode::Esc
-                        || (key.modifiers.contains(KeyModifiers::CONTROL)
-                            && key.code == KeyCode::Char('c'))
-                    {
-                        return Ok(());
-                    }
-                }
-            }
-            if last_tick.elapsed() >= tick_rate {
-                self.on_tick();
-                last_tick = Instant::now();
-            }
+    /// Only timebars that are relative to [`last_reset`](Self::last_reset) can be paused. The
+    /// [Minute](TimeBarLength::Minute), [Hour](TimeBarLength::Hour),
+    /// [Day](TimeBarLength::Day) and [Alarm](TimeBarLength::Alarm) timebars are bound to the wall
+    /// clock, which keeps going no matter what we do, so pausing them is ignored.
+    pub fn toggle_pause(&mut self, current_time: DateTime<Local>) {
+        let Some(len) = self.timebar_len() else {
+            return;
+        };
+        if len.is_wall_clock() {
+            debug!("ignoring pause, the {len:?} timebar follows the wall clock");
+            return;
+        }
+        if self.paused {
+            self.paused_total = self.paused_duration(current_time);
+            self.paused_at = None;
+            self.paused = false;
+            debug!("resumed, paused for {} in total", self.paused_total);
+        } else {
+            self.paused_at = Some(current_time);
+            self.paused = true;
+            debug!("paused at {current_time}");
         }
     }
-    fn on_tick(&mut self) {
-        self.maybe_reset_since_zero();
+
+    /// Start the timebar again right where the last one of `secs` should have ended
+    fn start_next_round(&mut self, secs: i64) {
+        self.last_reset =
+            Some(self.last_reset.unwrap() + chrono::Duration::seconds(secs) + self.paused_total);
+        self.paused_total = chrono::Duration::zero();
+        self.did_notify = false;
     }
-    #[allow(clippy::cast_possible_truncation)] // if we have that much padding, please truncate
-    fn ui(
-        &mut self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-        data: &Data,
-    ) -> anyhow::Result<()> {
-        terminal.draw(|frame| {
-            debug!("rendering the ui");
-            let root = frame.size();
-            let space = Block::bordered()
-                .padding(Padding::new(
-                    root.width / 16,
-                    root.width / 16,
-                    root.height / 16,
-                    root.height / 16,
-                ))
-                .title(env!("CARGO_PKG_NAME"))
-                .title_bottom(env!("CARGO_PKG_VERSION"))
-                .title_alignment(Alignment::Center)
-                .title_style(Style::new().bold());
-            let inner_rect = space.inner(root);
-            frame.render_widget(space, root);
-            let parts = Self::partition(inner_rect);
-
-            let mut clockw = tui_big_text::BigText::builder();
-            if inner_rect.width > 80 {
-                clockw.pixel_size(tui_big_text::PixelSize::Full);
-            } else {
-                clockw.pixel_size(tui_big_text::PixelSize::Quadrant);
-            }
 
-            let clockw = clockw
-                .style(Style::new().red())
-                .lines(vec![data.ftime().into()])
-                .alignment(Alignment::Center)
-                .build()
-                .expect("could not render time widget");
+    /// Does the timebar start over by itself after it went off?
+    ///
+    /// This is the case for a repeated countdown and for every alarm and step of the intervals
+    /// but the last.
+    fn goes_on(&self) -> bool {
+        self.config.repeat
+            || self.alarm_idx + 1 < self.config.alarm.len()
+            || self
+                .config
+                .intervals
+                .as_ref()
+                .is_some_and(|intervals| self.interval_idx + 1 < intervals.len())
+    }
 
-            // render the timebar which counts up to the full minute and so on
-            //
-            // Will not be rendered if it is None
-            let timebarw_padding = [
-                (f32::from(parts["timebarw"].width) * 0.43) as u16,
-                (f32::from(parts["timebarw"].width) * 0.25) as u16,
-            ];
-            let timebarw = ui::timebarw(self, data, &timebarw_padding, inner_rect);
-            let timebarw_label: Option<Paragraph> =
-                ui::timebarw_label(self, data, &timebarw_padding, inner_rect);
-
-            // render the small date
-            let datew = Paragraph::new(data.fdate())
-                .blue()
-                .block(Block::default().padding(Padding::right(2)))
-                .alignment(Alignment::Right);
-            frame.render_widget(&timebarw, parts["timebarw"]);
-            frame.render_widget(&timebarw_label, parts["timebarw_label"]);
-            frame.render_widget(datew, parts["datew"]);
-            // render the clock
-            frame.render_widget(clockw, parts["clockw"]);
-        })?;
-        debug!("done rendering the ui");
-        Ok(())
+    /// The step of the [intervals](ClockConfig::intervals) that is counted down right now
+    #[must_use]
+    pub fn interval_step(&self) -> Option<intervals::Step<'_>> {
+        self.config.intervals.as_ref()?.step(self.interval_idx)
     }
-    fn notify(&mut self) -> anyhow::Result<()> {
-        Self::beep()?;
-        #[cfg(feature = "sound")]
-        if self.sound {
-            std::thread::spawn(|| {
-                use rodio::{Decoder, OutputStream, Sink};
-                // only 30 KiB, so let's just include it in the binary and not worry about reading it
-                // from the fs and somehow making the file be there
-                const SOUND_RAW: &[u8] = include_bytes!("../data/media/alarm.mp3");
 
-                trace!("playing bundled sound");
+    /// Is the countdown, alarm or date reached at `current_time`?
+    ///
+    /// This compares whole seconds instead of the ratio, so the end is exact no matter how often
+    /// we look. The other timebars start over instead of ending.
+    #[must_use]
+    pub fn reached_end(&self, current_time: DateTime<Local>) -> bool {
+        matches!(
+            self.timebar_len(),
+            Some(
+                TimeBarLength::Countup(_)
+                    | TimeBarLength::Alarm(_)
+                    | TimeBarLength::Until(_)
+                    | TimeBarLength::Interval(_)
+            )
+        ) && self.timebar_remaining(current_time) == Some(0)
+    }
 
-                let sound_data: Cursor<_> = std::io::Cursor::new(SOUND_RAW);
+    /// Does the timebar blink at `current_time`, because it is done or nearly done?
+    ///
+    /// Not every terminal blinks by itself, so we redraw it every [half second](BLINK_RATE) while
+    /// it does.
+    #[must_use]
+    pub fn timebar_blinks(&self, current_time: DateTime<Local>) -> bool {
+        self.reached_end(current_time) || ui::nearly_done(self, current_time)
+    }
 
-                let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-                let sink = Sink::try_new(&stream_handle).unwrap();
-                sink.append(
-                    Decoder::new(sound_data).expect("could not decode the bundled alarm sound"),
-                );
-                sink.sleep_until_end();
+    /// Did the countdown, the last alarm or the last step go off, so that there is nothing left
+    /// to do?
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.did_notify && !self.goes_on()
+    }
 
-                debug!("played bundled sound");
-            });
+    /// Go off again in [`snooze`](Self::snooze) from now on
+    ///
+    /// Only the countdown and the last alarm stay finished after going off, so only those can be
+    /// snoozed. Snoozing again before it went off again adds to the snooze.
+    ///
+    /// # Panics
+    ///
+    /// The [snooze](ClockConfig::snooze) is too long to add to a time.
+    pub fn snooze(&mut self, current_time: DateTime<Local>) {
+        let (Some(len @ (TimeBarLength::Countup(_) | TimeBarLength::Alarm(_))), Some(last_reset)) =
+            (self.timebar_len(), self.last_reset)
+        else {
+            return;
+        };
+        if self.goes_on() {
+            debug!("ignoring snooze, the {len:?} timebar goes on by itself");
+            return;
         }
-        #[cfg(feature = "desktop")]
-        {
-            let mut notify = notify_rust::Notification::new();
-
-            notify.appname(env!("CARGO_BIN_NAME"));
+        if !self.did_notify && self.snoozed.is_zero() {
+            debug!("ignoring snooze, nothing went off yet");
+            return;
+        }
+        let elapsed =
+            current_time.signed_duration_since(last_reset) - self.paused_duration(current_time);
+        let target = elapsed.max(chrono::Duration::seconds(len.as_secs()))
+            + chrono::Duration::from_std(self.config.snooze).expect("snooze duration is too long");
+        self.snoozed = target - (chrono::Duration::seconds(len.as_secs()) - self.snoozed);
+        self.did_notify = false;
+        debug!("snoozed, {} longer in total", self.snoozed);
+    }
 
-            // see [FreeDesktop Sound Naming Specification](http://0pointer.de/public/sound-naming-spec.html)
-            // a sound exists for our use-case
-            //
-            // NOTE: sadly, notify_rust does not (yet) support KDE plasma, because
-            // they have a weird way of making sounds and notifications in general
-            // work. At least we get a little notification.
+    /// How long the timer has been running, without the pauses
+    ///
+    /// Nothing before the [setup](Self::setup), as the timer has not started yet.
+    #[must_use]
+    pub fn timer_elapsed(&self, current_time: DateTime<Local>) -> Option<chrono::Duration> {
+        Some(
+            current_time.signed_duration_since(self.last_reset?)
+                - self.paused_duration(current_time),
+        )
+    }
+
+    /// The time of the timer with milliseconds, like "01:02.345"
+    #[must_use]
+    pub fn stopwatch_time(&self, current_time: DateTime<Local>) -> String {
+        let elapsed = self
+            .timer_elapsed(current_time)
+            .unwrap_or_default()
+            .max(chrono::Duration::zero());
+        let (hours, minutes, seconds, millis) = (
+            elapsed.num_hours(),
+            elapsed.num_minutes() % 60,
+            elapsed.num_seconds() % 60,
+            elapsed.num_milliseconds() % 1000,
+        );
+        if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}.{millis:03}")
+        } else {
+            format!("{minutes:02}:{seconds:02}.{millis:03}")
+        }
+    }
+
+    /// The time left on the timebar for the [big countdown](ClockConfig::big_countdown), like
+    /// "04:59" or "1:00:00"
+    ///
+    /// The timer shows how long it has been running instead. Nothing without the big countdown or
+    /// before the [setup](Self::setup).
+    #[must_use]
+    pub fn countdown_time(&self, current_time: DateTime<Local>) -> Option<String> {
+        if !self.config.big_countdown {
+            return None;
+        }
+        let secs = match self.timebar_len()? {
+            TimeBarLength::Timer => self.timer_elapsed(current_time)?.num_seconds().max(0),
+            _ => self.timebar_remaining(current_time)?,
+        };
+        let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+        Some(if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            format!("{minutes:02}:{seconds:02}")
+        })
+    }
+
+    /// How long the current lap of the timer has been running, nothing before the
+    /// [setup](Self::setup)
+    #[must_use]
+    pub fn current_lap(&self, current_time: DateTime<Local>) -> Option<chrono::Duration> {
+        Some(self.timer_elapsed(current_time)? - self.laps.iter().sum())
+    }
+
+    /// Finish the current lap of the timer and start the next one
+    pub fn lap(&mut self, current_time: DateTime<Local>) {
+        if self.timebar_len() != Some(TimeBarLength::Timer) {
+            debug!("ignoring lap, there is no timer");
+            return;
+        }
+        let Some(lap) = self.current_lap(current_time) else {
+            debug!("ignoring lap, the timer has not started");
+            return;
+        };
+        debug!("lap {}: {lap}", self.laps.len() + 1);
+        self.laps.push(lap);
+    }
+
+    /// The laps of the timer, one line for each
+    #[must_use]
+    pub fn laps(&self) -> &[chrono::Duration] {
+        &self.laps
+    }
+
+    /// Print the laps of the timer, for when the TUI is gone
+    pub fn print_laps(&self) {
+        let mut total = chrono::Duration::zero();
+        for (i, lap) in self.laps.iter().enumerate() {
+            total += *lap;
+            println!(
+                "lap {}: {} (total {})",
+                i + 1,
+                format_duration(*lap),
+                format_duration(total)
+            );
+        }
+    }
+
+    /// When each thing the timebar notifies about happens, if it started now
+    ///
+    /// The pomodoro, custom and repeated timebars go on forever, so only their first
+    /// rounds are in it. The wall clock timebars have nothing to notify about. Nothing before the
+    /// [setup](Self::setup), as it is not known when the timebar starts.
+    #[must_use]
+    pub fn schedule(&self) -> Option<Vec<(DateTime<Local>, String)>> {
+        let start = self.last_reset?;
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+        let span = |secs: i64| {
+            humantime::Duration::from(std::time::Duration::from_secs(secs.unsigned_abs()))
+        };
+        let mut events = Vec::new();
+        match self.timebar_len() {
+            Some(TimeBarLength::Interval(_)) => {
+                let intervals = self.config.intervals.as_ref()?;
+                let mut offset = 0;
+                for step in (0..intervals.len()).filter_map(|idx| intervals.step(idx)) {
+                    let secs = step.len.as_secs() as i64;
+                    events.push((
+                        at(offset),
+                        format!(
+                            "{} {}/{} starts ({})",
+                            step.name,
+                            step.round,
+                            intervals.rounds,
+                            span(secs)
+                        ),
+                    ));
+                    offset += secs;
+                }
+                events.push((at(offset), "all intervals are done".to_string()));
+            }
+            Some(TimeBarLength::Pomodoro { work, break_ }) => {
+                for round in 0..SCHEDULE_ROUNDS {
+                    let offset = round * (work + break_);
+                    events.push((
+                        at(offset),
+                        format!("work {} starts ({})", round + 1, span(work)),
+                    ));
+                    events.push((
+                        at(offset + work),
+                        format!("break {} starts ({})", round + 1, span(break_)),
+                    ));
+                }
+            }
+            Some(TimeBarLength::Countup(secs)) => {
+                let rounds = if self.config.repeat {
+                    SCHEDULE_ROUNDS
+                } else {
+                    1
+                };
+                for round in 1..=rounds {
+                    events.push((
+                        at(round * secs),
+                        format!("the countdown of {} is up", span(secs)),
+                    ));
+                }
+            }
+            Some(TimeBarLength::Custom(millis)) => {
+                let span = humantime::Duration::from(std::time::Duration::from_millis(
+                    millis.unsigned_abs(),
+                ));
+                for round in 1..=SCHEDULE_ROUNDS {
+                    events.push((
+                        start + chrono::Duration::milliseconds(round * millis),
+                        format!("another {span} have passed"),
+                    ));
+                }
+            }
+            Some(TimeBarLength::Alarm(_)) => {
+                // each alarm counts from when the one before went off
+                let mut clock = self.clone();
+                let mut time = start;
+                for alarm in &self.config.alarm {
+                    time += chrono::Duration::seconds(clock.alarm_secs(*alarm));
+                    events.push((
+                        time,
+                        format!(
+                            "the alarm for {} goes off",
+                            alarm.format(self.time_format_short())
+                        ),
+                    ));
+                    clock.last_reset = Some(time);
+                }
+            }
+            Some(TimeBarLength::Until(secs)) => {
+                events.push((at(secs), "the date is reached".to_string()));
+            }
+            _ => (),
+        }
+        Some(events)
+    }
+
+    /// Print the [schedule](Self::schedule), for the [dry run](ClockConfig::dry_run)
+    pub fn print_schedule(&self) {
+        let events = self.schedule().unwrap_or_default();
+        if events.is_empty() {
+            println!("nothing to notify about, the clock just shows the time");
+            return;
+        }
+        let format = format!("{} {}", self.date_format(), self.time_format());
+        for (time, event) in events {
+            println!(
+                "{}  {event}",
+                self.localized(&self.in_timezone(time), &format)
+            );
+        }
+        if self.config.pomodoro || self.config.custom.is_some() || self.config.repeat {
+            println!("and so on, until you quit");
+        }
+    }
+
+    /// Restart the timebar from zero
+    ///
+    /// This also clears a notification that was already sent, any pause, any snooze and the
+    /// laps.
+    pub fn reset(&mut self) {
+        self.setup_last_reset();
+        self.setup_chess();
+        self.laps.clear();
+        self.snoozed = chrono::Duration::zero();
+        self.did_notify = false;
+        self.paused = false;
+        self.paused_at = None;
+        self.paused_total = chrono::Duration::zero();
+        self.setup_stopwatch();
+        debug!("reset the timebar");
+    }
+
+    /// Stop the timer right where it starts, if it should start [stopped](ClockConfig::stopped)
+    fn setup_stopwatch(&mut self) {
+        if self.config.stopped && self.timebar_len() == Some(TimeBarLength::Timer) {
+            self.paused = true;
+            self.paused_at = self.last_reset;
+        }
+    }
+
+    fn setup_last_reset(&mut self) {
+        if let Some(len) = self.timebar_len() {
+            trace!("Local Time: {}", self.now());
+            // a phase shifts the wall clock timebars, as if the clock was that much behind
+            let wall_now = self.in_timezone(self.now()) - self.phase();
+            match len {
+                TimeBarLength::Timer if self.config.since.is_some() => {
+                    self.last_reset = self.config.since.map(|since| since.trunc_subsecs(0));
+                }
+                TimeBarLength::Custom(_)
+                | TimeBarLength::Countup(_)
+                | TimeBarLength::Timer
+                | TimeBarLength::Pomodoro { .. }
+                | TimeBarLength::Interval(_)
+                | TimeBarLength::Alarm(_)
+                | TimeBarLength::Until(_) => {
+                    // whole seconds, so that the timebar is full exactly when the clock shows it
+                    self.last_reset = Some(self.now().trunc_subsecs(0));
+                }
+                TimeBarLength::Minute => {
+                    self.last_reset = Some(
+                        wall_now
+                            .trunc_subsecs(0)
+                            .with_second(0)
+                            .expect("tried to use a time that does not exist")
+                            .with_timezone(&Local)
+                            + self.phase(),
+                    );
+                }
+                TimeBarLength::Hour => {
+                    self.last_reset = Some(
+                        wall_now
+                            .trunc_subsecs(0)
+                            .with_second(0)
+                            .expect("tried to use a time that does not exist")
+                            .with_minute(0)
+                            .expect("tried to use a time that does not exist")
+                            .with_timezone(&Local)
+                            + self.phase(),
+                    );
+                }
+                TimeBarLength::Day => {
+                    self.last_reset = Some(
+                        wall_now
+                            .trunc_subsecs(0)
+                            .with_second(0)
+                            .expect("tried to use a time that does not exist")
+                            .with_minute(0)
+                            .expect("tried to use a time that does not exist")
+                            .with_hour(0)
+                            .expect("tried to use a time that does not exist")
+                            .with_timezone(&Local)
+                            + self.phase(),
+                    );
+                }
+                TimeBarLength::Week | TimeBarLength::Month(_) | TimeBarLength::Year(_) => {
+                    self.last_reset = Some(self.calendar_start(len, self.now()));
+                }
+            }
+            debug!("set up initial last reset as {:#?}", self.last_reset);
+        }
+    }
+
+    /// Get ready for running, this starts the timebar
+    ///
+    /// # Errors
+    ///
+    /// The time or date format is invalid, or the tick rate is zero.
+    pub fn setup(&mut self) -> anyhow::Result<()> {
+        Self::validate_format("time", self.time_format())?;
+        Self::validate_format("date", self.date_format())?;
+        if self.config.tick_rate.is_zero() {
+            return Err(anyhow!("the tick rate must be longer than zero"));
+        }
+        if let Some(server) = &self.config.ntp {
+            ntp::spawn(server.clone(), Arc::clone(&self.ntp_offset));
+        }
+        self.sort_alarms(self.in_timezone(self.now()).time());
+        self.validate_phase()?;
+        self.setup_color();
+        self.setup_keys()?;
+        #[cfg(feature = "sound")]
+        self.setup_volume();
+        if self.config.since.is_some_and(|since| since > self.now()) {
+            warn!(
+                "{:?} is in the future, starting the timer now",
+                self.config.since
+            );
+            self.config.since = Some(self.now());
+        }
+        if self.config.new_year {
+            self.config.until = Some(self.next_new_year());
+        }
+        self.setup_last_reset();
+        self.setup_chess();
+        self.setup_stopwatch();
+        self.resumed_started_at = self.config.since;
+        if self.config.resume {
+            self.resumed_started_at = Some(State::load()?.restore(self)?);
+        }
+        if self.timebar_len() == Some(TimeBarLength::Until(0)) {
+            debug!("{:?} has already passed, not notifying", self.config.until);
+            self.did_notify = true;
+        }
+        Ok(())
+    }
+
+    /// How much the [phase](ClockConfig::phase) shifts the wall clock timebars
+    fn phase(&self) -> chrono::Duration {
+        self.config
+            .phase
+            .map_or_else(chrono::Duration::zero, |phase| {
+                chrono::Duration::from_std(phase).expect("the phase is way too long")
+            })
+    }
+
+    fn validate_phase(&self) -> anyhow::Result<()> {
+        let Some(phase) = self.config.phase else {
+            return Ok(());
+        };
+        match self.timebar_len() {
+            Some(len @ (TimeBarLength::Minute | TimeBarLength::Hour | TimeBarLength::Day))
+                if phase.as_secs() >= len.as_secs().unsigned_abs() =>
+            {
+                Err(anyhow!(
+                    "the phase of {} must be shorter than the {len} timebar",
+                    humantime::Duration::from(phase)
+                ))
+            }
+            Some(TimeBarLength::Minute | TimeBarLength::Hour | TimeBarLength::Day) => Ok(()),
+            _ => Err(anyhow!(
+                "the phase only works with the minute, hour or day timebar"
+            )),
+        }
+    }
+
+    fn setup_chess(&mut self) {
+        self.chess = self.config.chess.map(|time| {
+            chess::ChessState::new(
+                chrono::Duration::from_std(time).expect("the chess clock is way too long"),
+            )
+        });
+    }
+
+    fn setup_keys(&mut self) -> anyhow::Result<()> {
+        self.keymap = KeyMap::new(|action| {
+            match action {
+                Action::Quit => &self.config.key_quit,
+                Action::Seconds => &self.config.key_seconds,
+                Action::Lap => &self.config.key_lap,
+                Action::Snooze => &self.config.key_snooze,
+                Action::Pause => &self.config.key_pause,
+                Action::Reset => &self.config.key_reset,
+                Action::Switch => &self.config.key_switch,
+            }
+            .clone()
+        })?;
+        debug!("keys: {:?}", self.keymap);
+        Ok(())
+    }
+
+    fn setup_color(&mut self) {
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let dumb_term = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+        if no_color_env || dumb_term {
+            debug!("the environment asks for no colors");
+            self.config.no_color = true;
+        }
+        if self.config.no_color {
+            self.config.palette = Palette::Mono;
+            self.custom_color = None;
+            return;
+        }
+        self.custom_color = self.config.color.as_deref().and_then(|color| {
+            Color::from_str(color)
+                .inspect_err(|_| warn!("unknown color '{color}', using the default colors"))
+                .ok()
+        });
+    }
+
+    #[cfg(feature = "sound")]
+    fn setup_volume(&mut self) {
+        if !(0.0..=1.0).contains(&self.config.volume) {
+            let clamped = self.config.volume.clamp(0.0, 1.0);
+            warn!(
+                "volume {} is out of range, using {clamped}",
+                self.config.volume
+            );
+            self.config.volume = clamped;
+        }
+    }
+
+    /// Is the timezone shown in a corner? Only if it is not the local one, or the time in UTC
+    /// is shown too, or the user asked for it
+    #[must_use]
+    pub const fn shows_zone(&self) -> bool {
+        self.config.show_tz || self.config.timezone.is_some() || self.config.with_utc
+    }
+
+    /// The color of the clock digits
+    #[must_use]
+    pub fn digit_color(&self) -> Color {
+        self.custom_color
+            .unwrap_or_else(|| self.config.palette.digits())
+    }
+
+    /// Turn off all timebars, so that another one can be chosen
+    pub(crate) fn clear_timebar(&mut self) {
+        (self.config.minute, self.config.hour, self.config.day) = (false, false, false);
+        (self.config.week, self.config.month, self.config.year) = (false, false, false);
+        (self.config.timer, self.config.pomodoro) = (false, false);
+        (self.config.custom, self.config.countdown, self.config.until) = (None, None, None);
+        (self.config.since, self.config.new_year) = (None, false);
+        self.config.alarm.clear();
+        (self.config.chess, self.config.intervals) = (None, None);
+    }
+
+    /// Add `record` to the [history](ClockConfig::log_history), if it is kept
+    ///
+    /// Like with saving the state, errors are only logged.
+    fn log_history(&self, record: &Record) {
+        if let Some(format) = self.config.log_history {
+            let _ = record.append(format).inspect_err(|e| {
+                warn!("could not log the history: {e}");
+                debug!("complete error: {e:#?}");
+            });
+        }
+    }
+
+    /// Log the [timer](ClockConfig::timer) to the [history](ClockConfig::log_history) when we quit
+    pub fn log_stopwatch(&self) {
+        if let Some(record) = Record::stopwatch(self, self.now()) {
+            self.log_history(&record);
+        }
+    }
+
+    /// Save the running timebar, so that it can be [resumed](ClockConfig::resume) later
+    ///
+    /// Saving is not important enough to stop the clock, so errors are only logged. The saved
+    /// state is kept if the current timebar can't be resumed anyway.
+    pub fn save_state(&self, data: &Data) {
+        if let Some(state) = State::new(self, *data.started_at()) {
+            let _ = state.save().inspect_err(|e| {
+                warn!("could not save the state: {e}");
+                debug!("complete error: {e:#?}");
+            });
+        }
+    }
+
+    /// Make sure a format can be rendered, so that we don't crash while running
+    fn validate_format(kind: &str, format: &str) -> anyhow::Result<()> {
+        let mut buf = String::new();
+        write!(buf, "{}", Local::now().format(format))
+            .map_err(|_| anyhow!("invalid {kind} format: '{format}'"))?;
+        Ok(())
+    }
+
+    /// Run the clock TUI
+    ///
+    /// # Errors
+    ///
+    /// * The [setup](Self::setup) fails
+    /// * The [signal handlers](Self::register_signals) could not be registered
+    /// * Drawing the [ui](Self::ui) fails
+    /// * Polling or reading an event fails
+    pub fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> anyhow::Result<()> {
+        self.setup()?;
+        self.register_signals()?;
+        #[cfg(feature = "sound")]
+        if self.config.tick_sound {
+            self.ticker = Some(tick::Ticker::spawn(
+                self.config.volume,
+                self.config.audio_device.clone(),
+            ));
+        }
+        let mut data = self.new_data();
+        data.set_smooth_bar(self.config.smooth_bar);
+        if self.config.set_title {
+            // push the old title onto the stack of the terminal, so we can bring it back
+            write!(terminal.backend_mut(), "\x1b[22;2t")?;
+        }
+        let result = self.run_loop(terminal, &mut data);
+        if self.config.set_title {
+            write!(terminal.backend_mut(), "\x1b[23;2t")?;
+            terminal.backend_mut().flush()?;
+        }
+        self.save_state(&data);
+        self.log_stopwatch();
+        result
+    }
+
+    fn run_loop(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        data: &mut Data,
+    ) -> anyhow::Result<()> {
+        let mut finished_at: Option<Instant> = None;
+        let mut saved_at = Instant::now();
+        loop {
+            if self.terminated() {
+                return Ok(());
+            }
+            let now = self.update(data);
+            #[cfg(feature = "sound")]
+            self.maybe_tick(now);
+            self.notify_when_done(now);
+            if self.chess.as_mut().is_some_and(|chess| chess.flag(now)) {
+                self.finish();
+            }
+            // the flash and the blinking go on even if the time did not change, and the chess
+            // clocks are not the shown time
+            if data.changed()
+                || self.flash_frames > 0
+                || self.chess.is_some()
+                || self.timebar_blinks(now)
+            {
+                self.ui(terminal, data)?;
+                if self.config.set_title {
+                    self.update_window_title(terminal, data)?;
+                }
+            }
+            if saved_at.elapsed() >= SAVE_INTERVAL {
+                self.save_state(data);
+                saved_at = Instant::now();
+            }
+            if self.config.exit_on_finish {
+                // snoozing during the delay keeps us running
+                finished_at = finished_at.filter(|_| self.is_finished());
+                if self.is_finished()
+                    && finished_at.get_or_insert_with(Instant::now).elapsed()
+                        >= self.config.exit_delay
+                {
+                    debug!("the countdown is finished, exiting");
+                    return Ok(());
+                }
+            }
+            if poll(self.next_wakeup(self.now()))? {
+                let action = match event::read()? {
+                    Event::Key(key) => self.keymap.action(&key),
+                    Event::Mouse(mouse) if self.config.mouse => {
+                        self.mouse_action(mouse, terminal.size()?, data)
+                    }
+                    // the time did not change, draw what we have again in the new size
+                    Event::Resize(..) => {
+                        self.ui(terminal, data)?;
+                        None
+                    }
+                    _ => None,
+                };
+                match action {
+                    Some(Action::Quit) => return Ok(()),
+                    // without seconds, the formatted time and with it the ui only changes
+                    // once per minute
+                    Some(Action::Seconds) => self.show_seconds ^= true,
+                    Some(Action::Lap) => self.lap(now),
+                    Some(Action::Snooze) => self.snooze(now),
+                    Some(Action::Pause | Action::Switch) if self.chess.is_some() => {
+                        // the turn ends when the key is pressed, not when we last drew
+                        let pressed_at = self.now();
+                        if let Some(chess) = &mut self.chess {
+                            chess.switch(pressed_at);
+                        }
+                    }
+                    Some(Action::Pause) => self.toggle_pause(now),
+                    Some(Action::Reset) => {
+                        self.reset();
+                        if let Some(TimeBarLength::Countup(_) | TimeBarLength::Timer) =
+                            self.timebar_len()
+                        {
+                            data.reset_started_at(self.now());
+                        }
+                    }
+                    // there are no turns outside of the chess clock
+                    Some(Action::Switch) | None => (),
+                }
+            }
+        }
+    }
+
+    /// The timebars that are shown stacked instead of a single timebar
+    #[must_use]
+    pub fn stacked_bars(&self) -> Vec<TimeBarLength> {
+        if self.config.all_bars {
+            vec![
+                TimeBarLength::Minute,
+                TimeBarLength::Hour,
+                TimeBarLength::Day,
+            ]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// How full the `len` timebar is at `current_time`, from 0.0 to 1.0
+    ///
+    /// Unlike [`timebar_ratio`](Self::timebar_ratio), this does not need
+    /// [`last_reset`](Self::last_reset), so it only works for the minute, hour and day, which
+    /// start at a fixed time on the wall clock. Other timebars are always empty.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // a day has not that many seconds
+    pub fn wall_ratio(&self, len: TimeBarLength, current_time: DateTime<Local>) -> f64 {
+        let wall_time = self.in_timezone(current_time);
+        let since = match len {
+            TimeBarLength::Minute => wall_time.second(),
+            TimeBarLength::Hour => wall_time.minute() * 60 + wall_time.second(),
+            TimeBarLength::Day => wall_time.num_seconds_from_midnight(),
+            _ => 0,
+        };
+        f64::from(since) / len.as_secs() as f64
+    }
+
+    /// Go on to the current time and put what should be shown into `data`
+    ///
+    /// Returns the current time as it is shown.
+    fn update(&mut self, data: &mut Data) -> DateTime<Local> {
+        // everything below quietly shows no timebar without it, which would hide the mistake
+        debug_assert!(
+            self.last_reset.is_some() || self.timebar_len().is_none(),
+            "the clock was not set up before it was updated"
+        );
+        // reset first, so that the timebar starts at 0% and not at 100% on the boundary
+        self.on_tick();
+        // we wake up just after the whole second, rounding might skip ahead to the next one
+        let now = if self.config.ms || self.smooth_bar_moving() || self.timebar_blinks(self.now()) {
+            self.now()
+        } else {
+            self.now().trunc_subsecs(0)
+        };
+        let wall_time = self.in_timezone(now);
+        let ftime = self.localized(&wall_time, self.time_format());
+        let (fdate, ftime) = if self.config.ms {
+            (self.fdate(wall_time), self.stopwatch_time(now))
+        } else if let Some(countdown) = self.countdown_time(now) {
+            // the time of day moves next to the date
+            (format!("{} {ftime}", self.fdate(wall_time)), countdown)
+        } else {
+            (self.fdate(wall_time), ftime)
+        };
+        data.update(now, fdate, ftime, self.timebar_ratio(now));
+        data.set_stacked_ratios(
+            self.stacked_bars()
+                .into_iter()
+                .map(|len| self.wall_ratio(len, now))
+                .collect(),
+        );
+        now
+    }
+
+    /// Render a single frame of `width` and `height` as text, like it would be shown in the TUI
+    ///
+    /// Colors and other styles are left out.
+    ///
+    /// # Errors
+    ///
+    /// The [setup](Self::setup) fails.
+    pub fn render_once(&mut self, width: u16, height: u16) -> anyhow::Result<String> {
+        self.setup()?;
+        let mut data = self.new_data();
+        self.update(&mut data);
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        ui::ClockWidget::new(self, &data).render(area, &mut buf);
+        Ok(ui::buffer_to_string(&buf))
+    }
+
+    /// What to do for a click, if anything
+    ///
+    /// A left click on the timebar restarts it, a left click on the clock pauses or resumes it.
+    /// `area` is where the clock is shown.
+    #[must_use]
+    pub fn mouse_action(&self, mouse: MouseEvent, area: Rect, data: &Data) -> Option<Action> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || !self.config.world.is_empty() {
+            return None;
+        }
+        let areas = ui::ClockWidget::new(self, data).areas(area);
+        let position = Position::new(mouse.column, mouse.row);
+        if areas["timebarw"].contains(position) || areas["timebarw_label"].contains(position) {
+            Some(Action::Reset)
+        } else if areas["clockw"].contains(position) {
+            Some(Action::Pause)
+        } else {
+            None
+        }
+    }
+
+    /// Quit cleanly on SIGINT and SIGTERM, so that the terminal is restored
+    ///
+    /// The loops have to check [`terminated`](Self::terminated) for this to work.
+    ///
+    /// # Errors
+    ///
+    /// The signal handlers could not be registered.
+    pub fn register_signals(&self) -> anyhow::Result<()> {
+        for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+            signal_hook::flag::register(signal, Arc::clone(&self.terminate))?;
+        }
+        Ok(())
+    }
+
+    /// Did we get a signal to quit?
+    #[must_use]
+    pub fn terminated(&self) -> bool {
+        let terminated = self.terminate.load(Ordering::Relaxed);
+        if terminated {
+            debug!("got a signal to quit");
+        }
+        terminated
+    }
+
+    /// How long we can sleep until something needs to be done, if no key is pressed
+    ///
+    /// The shown time changes and the wall clock timebars reset at the next whole second. The
+    /// custom and pomodoro timebars reset when they are full, which is not on a whole second.
+    #[must_use]
+    pub fn next_wakeup(&self, current_time: DateTime<Local>) -> std::time::Duration {
+        if self.config.ms || self.smooth_bar_moving() {
+            return MS_TICK_RATE.min(self.config.tick_rate);
+        }
+        if self.flash_frames > 0 {
+            return FLASH_RATE;
+        }
+        let until_second = std::time::Duration::from_nanos(
+            1_000_000_000 - u64::from(current_time.nanosecond() % 1_000_000_000),
+        );
+        let until_end = match (self.timebar_len(), self.last_reset) {
+            (
+                Some(len @ (TimeBarLength::Custom(_) | TimeBarLength::Pomodoro { .. })),
+                Some(last_reset),
+            ) if !self.paused => {
+                let end = last_reset
+                    + self.paused_total
+                    + chrono::Duration::milliseconds(len.phase_millis(self.pomodoro_on_break));
+                // already over if negative
+                end.signed_duration_since(current_time).to_std().ok()
+            }
+            _ => None,
+        };
+        let until_turn = self
+            .chess
+            .as_ref()
+            .and_then(|chess| chess.until_next_second(current_time));
+        #[allow(clippy::cast_possible_truncation)] // half a second in nanoseconds fits
+        let blink_nanos = BLINK_RATE.as_nanos() as u32;
+        let until_blink = self.timebar_blinks(current_time).then(|| {
+            std::time::Duration::from_nanos(u64::from(
+                blink_nanos - current_time.nanosecond() % blink_nanos,
+            ))
+        });
+        [until_end, until_turn, until_blink]
+            .into_iter()
+            .flatten()
+            .fold(until_second, std::time::Duration::min)
+            .min(self.config.tick_rate)
+    }
+    fn on_tick(&mut self) {
+        self.maybe_reset_since_zero(self.now());
+        self.maybe_chime(self.now());
+        self.maybe_notify_milestones(self.now());
+        self.maybe_prewarn(self.now());
+        self.flash_frames = self.flash_frames.saturating_sub(1);
+    }
+
+    /// Beep once for each of the last seconds of the countdown, see [prewarn](ClockConfig::prewarn)
+    ///
+    /// There is no beep when nothing is left, the countdown notifies about that itself.
+    pub fn maybe_prewarn(&mut self, current_time: DateTime<Local>) {
+        let Some(prewarn) = self.config.prewarn else {
+            return;
+        };
+        if !matches!(self.timebar_len(), Some(TimeBarLength::Countup(_))) {
+            return;
+        }
+        let Some(remaining) = self.timebar_remaining(current_time) else {
+            return;
+        };
+        #[allow(clippy::cast_possible_wrap)] // nobody prewarns for that long
+        let window = prewarn.as_secs() as i64;
+        if remaining == 0 || remaining > window || self.last_prewarn == Some(remaining) {
+            return;
+        }
+        self.last_prewarn = Some(remaining);
+        debug!("prewarn, {remaining}s left");
+        if self.rings_bell() {
+            let _ = Self::beep().inspect_err(|e| error!("could not beep: {e}"));
+        }
+    }
+
+    /// Click once per second with the [tick sound](Self::tick_sound)
+    #[cfg(feature = "sound")]
+    fn maybe_tick(&mut self, current_time: DateTime<Local>) {
+        let Some(ticker) = &self.ticker else {
+            return;
+        };
+        let second = current_time.trunc_subsecs(0);
+        if self.last_tick != Some(second) {
+            ticker.tick();
+            self.last_tick = Some(second);
+        }
+    }
+
+    /// Does the [smooth timebar](ClockConfig::smooth_bar) move right now?
+    ///
+    /// The timer and a paused timebar stand still, so there is no need to redraw them so often.
+    fn smooth_bar_moving(&self) -> bool {
+        self.config.smooth_bar
+            && !self.paused
+            && self
+                .timebar_len()
+                .is_some_and(|len| len != TimeBarLength::Timer)
+    }
+
+    /// Is the screen inverted right now, because the [flash](ClockConfig::flash) is going on?
+    #[must_use]
+    pub const fn flash_inverted(&self) -> bool {
+        self.flash_frames > 0 && self.flash_frames.is_multiple_of(2)
+    }
+
+    /// Beep if a new hour started, or if there are beeps left for it
+    ///
+    /// This only beeps once per second at most, no matter how often it is called.
+    pub fn maybe_chime(&mut self, current_time: DateTime<Local>) {
+        if !self.config.chime {
+            return;
+        }
+        let hour = self.in_timezone(current_time).hour();
+        if self.chime_hour.is_some_and(|last| last != hour) {
+            self.chimes_left = if self.config.chime_count {
+                match hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                }
+            } else {
+                1
+            };
+            debug!("it is {hour} o'clock, chiming {} times", self.chimes_left);
+        }
+        self.chime_hour = Some(hour);
+        if self.chimes_left > 0
+            && self.last_chime.is_none_or(|last| {
+                current_time.signed_duration_since(last) >= chrono::Duration::seconds(1)
+            })
+        {
+            self.chimes_left -= 1;
+            self.last_chime = Some(current_time);
+            if self.rings_bell() {
+                let _ = Self::beep().inspect_err(|e| error!("could not chime: {e}"));
+            }
+        }
+    }
+    fn ui(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        data: &Data,
+    ) -> anyhow::Result<()> {
+        terminal.draw(|frame| {
+            debug!("rendering the ui");
+            frame.render_widget(ui::ClockWidget::new(self, data), frame.size());
+        })?;
+        debug!("done rendering the ui");
+        Ok(())
+    }
+    /// What the [window title](ClockConfig::set_title) says, like "4m 59s left - crock"
+    ///
+    /// Countdowns show what is left of them, everything else the time.
+    #[must_use]
+    pub fn window_title(&self, data: &Data) -> String {
+        let text = match (self.timebar_len(), self.timebar_remaining(*data.now())) {
+            // the wall clock timebars start over, what is left of them says little
+            (
+                Some(
+                    TimeBarLength::Countup(_)
+                    | TimeBarLength::Custom(_)
+                    | TimeBarLength::Pomodoro { .. }
+                    | TimeBarLength::Interval(_)
+                    | TimeBarLength::Alarm(_)
+                    | TimeBarLength::Until(_),
+                ),
+                Some(remaining),
+            ) => {
+                format!("{} left", ui::exact_duration(remaining))
+            }
+            _ => data.ftime().to_owned(),
+        };
+        format!(
+            "{text} - {}",
+            self.config
+                .title
+                .as_deref()
+                .unwrap_or(env!("CARGO_PKG_NAME"))
+        )
+    }
+    /// Set the title of the terminal window with OSC 2, if it changed
+    fn update_window_title(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        data: &Data,
+    ) -> anyhow::Result<()> {
+        let title = self.window_title(data);
+        if self.window_title.as_ref() != Some(&title) {
+            write!(terminal.backend_mut(), "\x1b]2;{title}\x07")?;
+            terminal.backend_mut().flush()?;
+            self.window_title = Some(title);
+        }
+        Ok(())
+    }
+    /// Notify once the countdown or alarm is up at `current_time`, see
+    /// [`reached_end`](Self::reached_end)
+    ///
+    /// The repeating timebars, the pomodoro and the custom timebar, notify whenever they start
+    /// over instead, see [`maybe_reset_since_zero`](Self::maybe_reset_since_zero).
+    pub fn notify_when_done(&mut self, current_time: DateTime<Local>) {
+        if !self.did_notify && self.reached_end(current_time) {
+            self.finish();
+            self.did_notify = true;
+        }
+    }
+    /// Notify once for the milestones of [notify at](ClockConfig::notify_at) that the timebar
+    /// passed at `current_time`
+    ///
+    /// Each milestone is only notified about once per run of the timebar. If the timebar is
+    /// already past some of them when it starts, like when it was resumed, those are skipped.
+    pub fn maybe_notify_milestones(&mut self, current_time: DateTime<Local>) {
+        if self.config.notify_at.is_empty() || self.timebar_len() == Some(TimeBarLength::Timer) {
+            return;
+        }
+        let Some(ratio) = self.timebar_ratio(current_time) else {
+            return;
+        };
+        let passed = self
+            .config
+            .notify_at
+            .iter()
+            .filter(|percent| ratio * 100.0 >= f64::from(**percent))
+            .fold(0u128, |bits, percent| bits | 1 << percent);
+        if self.milestones_reset != self.last_reset {
+            self.milestones_reset = self.last_reset;
+            self.milestones = passed;
+            return;
+        }
+        let new = passed & !self.milestones;
+        if new == 0 {
+            return;
+        }
+        self.milestones |= new;
+        // only the highest one, if we passed more than one at once
+        let percent = new.ilog2();
+        let left = self
+            .timebar_remaining(current_time)
+            .map_or_else(String::new, |secs| {
+                format!(", {} left", ui::exact_duration(secs))
+            });
+        let summary = if percent == 50 {
+            format!("Halfway there{left}.")
+        } else {
+            format!("{percent}% done{left}.")
+        };
+        debug!("passed the milestone {percent}%");
+        let _ = self
+            .alert(NotifyKind::Milestone, &summary)
+            .inspect_err(|e| {
+                error!("could not notify: {e}");
+                debug!("complete error: {e:#?}");
+            });
+    }
+    /// Let the user know that the timebar is done, in every way they want
+    fn finish(&mut self) {
+        if let Some(record) = Record::finished(self, self.now()) {
+            self.log_history(&record);
+        }
+        let _ = self.notify().inspect_err(|e| {
+            error!("could not notify: {e}");
+            debug!("complete error: {e:#?}");
+        });
+        if (self.config.flash || self.config.new_year) && !self.config.json {
+            self.flash_frames = FLASH_FRAMES;
+        }
+        if self.config.new_year && self.rings_bell() {
+            std::thread::spawn(|| {
+                let (bells, pause) = NEW_YEAR_BELLS;
+                for _ in 0..bells {
+                    std::thread::sleep(pause);
+                    let _ = Self::beep();
+                }
+            });
+        }
+    }
+    /// The first moment of next year, in the [timezone](ClockConfig::timezone) of the clock
+    ///
+    /// # Panics
+    ///
+    /// The new year does not exist in the timezone, which would be quite a surprise.
+    #[must_use]
+    pub fn next_new_year(&self) -> DateTime<Local> {
+        let year = self.in_timezone(self.now()).year() + 1;
+        let midnight = NaiveDate::from_ymd_opt(year, 1, 1)
+            .expect("the year is out of range")
+            .and_time(NaiveTime::MIN);
+        self.config
+            .timezone
+            .map_or_else(
+                || midnight.and_local_timezone(Local).earliest(),
+                |tz| {
+                    midnight
+                        .and_local_timezone(tz)
+                        .earliest()
+                        .map(|time| time.with_timezone(&Local))
+                },
+            )
+            .expect("the new year does not exist in the timezone")
+    }
+    /// How long the timebar that is done was, without the snoozed time
+    #[must_use]
+    pub fn finished_duration(&self) -> Option<std::time::Duration> {
+        let millis = match self.timebar_len()? {
+            TimeBarLength::Countup(_) if self.config.countdown.is_some() => {
+                return self.config.countdown
+            }
+            // it already switched to the next phase
+            len @ TimeBarLength::Pomodoro { .. } => len.phase_millis(!self.pomodoro_on_break),
+            len => len.as_millis(),
+        };
+        Some(std::time::Duration::from_millis(millis.unsigned_abs()))
+    }
+    /// What the desktop notification says when the timebar is done
+    #[must_use]
+    pub fn notify_summary(&self) -> String {
+        let duration = humantime::Duration::from(self.finished_duration().unwrap_or_default());
+        #[cfg(feature = "desktop")]
+        if let Some(message) = &self.config.notify_message {
+            #[allow(clippy::literal_string_with_formatting_args)] // our own placeholder
+            return message.replace("{duration}", &duration.to_string());
+        }
+        if let Some(loser) = self.chess.as_ref().and_then(chess::ChessState::loser) {
+            return format!("Player {} ran out of time.", loser + 1);
+        }
+        match self.timebar_len() {
+            Some(TimeBarLength::Pomodoro { .. }) if self.pomodoro_on_break => {
+                "Work is done, time for a break.".to_string()
+            }
+            Some(TimeBarLength::Pomodoro { .. }) => "The break is over, back to work.".to_string(),
+            Some(TimeBarLength::Alarm(_)) => format!(
+                "Your alarm for {} is up.",
+                self.config.alarm[self.alarm_idx].format(self.time_format_short())
+            ),
+            Some(TimeBarLength::Until(_)) if self.config.new_year => {
+                format!("Happy new year {}!", self.in_timezone(self.now()).year())
+            }
+            Some(TimeBarLength::Until(_)) => format!(
+                "It is {} now.",
+                self.config.until.unwrap_or_default().format(&format!(
+                    "{} {}",
+                    self.date_format(),
+                    self.time_format_short()
+                ))
+            ),
+            Some(TimeBarLength::Countup(_)) => format!("Your countdown of {duration} is up."),
+            Some(TimeBarLength::Interval(_)) if self.goes_on() => format!(
+                "{} is over, now {}.",
+                self.interval_step().map_or("", |step| step.name),
+                self.config
+                    .intervals
+                    .as_ref()
+                    .and_then(|intervals| intervals.step(self.interval_idx + 1))
+                    .map_or("", |step| step.name)
+            ),
+            Some(TimeBarLength::Interval(_)) => "All intervals are done.".to_string(),
+            Some(TimeBarLength::Custom(_)) => format!("Another {duration} have passed."),
+            Some(len) => format!("The {len} is over."),
+            None => "Time is up.".to_string(),
+        }
+    }
+    /// What kind of notification it is when the timebar is done
+    #[must_use]
+    pub fn notify_kind(&self) -> NotifyKind {
+        match self.timebar_len() {
+            // it already switched to the next phase
+            Some(TimeBarLength::Pomodoro { .. }) if self.pomodoro_on_break => NotifyKind::WorkOver,
+            Some(TimeBarLength::Pomodoro { .. }) => NotifyKind::BreakOver,
+            _ => NotifyKind::Done,
+        }
+    }
+    /// The sound for the `kind` of notification
+    ///
+    /// That is the sound for the kind if there is one, or else the
+    /// [sound file](ClockConfig::sound_file), or else the [bundled one](NotifyKind::bundled_sound).
+    #[cfg(feature = "sound")]
+    #[must_use]
+    pub fn sound_for(&self, kind: NotifyKind) -> sound::Sound {
+        let sound = match kind {
+            NotifyKind::Done => &self.config.sound_done,
+            NotifyKind::WorkOver => &self.config.sound_work_over,
+            NotifyKind::BreakOver => &self.config.sound_break_over,
+            NotifyKind::Milestone => &self.config.sound_milestone,
+        };
+        sound.clone().unwrap_or_else(|| {
+            self.config.sound_file.clone().map_or_else(
+                || sound::Sound::Bundled(kind.bundled_sound()),
+                sound::Sound::File,
+            )
+        })
+    }
+    fn notify(&self) -> anyhow::Result<()> {
+        if let Some(command) = &self.config.exec {
+            Self::exec(command.clone());
+        }
+        self.alert(self.notify_kind(), &self.notify_summary())
+    }
+    /// Beep, play the sound for the `kind` and show a desktop notification that says `summary`
+    #[cfg_attr(not(feature = "sound"), allow(unused_variables))]
+    fn alert(&self, kind: NotifyKind, summary: &str) -> anyhow::Result<()> {
+        if self.rings_bell() {
+            Self::beep()?;
+        }
+        #[cfg(feature = "sound")]
+        if self.config.sound {
+            let volume = self.config.volume;
+            let sound = self.sound_for(kind);
+            let audio_device = self.config.audio_device.clone();
+            std::thread::spawn(move || {
+                let _ = sound
+                    .play(volume, audio_device.as_deref())
+                    .inspect_err(|e| {
+                        error!("could not play the sound: {e}");
+                        debug!("complete error: {e:#?}");
+                    });
+            });
+        }
+        #[cfg(feature = "desktop")]
+        {
+            let mut notify = notify_rust::Notification::new();
+
+            notify.appname(env!("CARGO_PKG_NAME"));
+
+            // see [FreeDesktop Sound Naming Specification](http://0pointer.de/public/sound-naming-spec.html)
+            // a sound exists for our use-case
+            //
+            // NOTE: sadly, notify_rust does not (yet) support KDE plasma, because
+            // they have a weird way of making sounds and notifications in general
+            // work. At least we get a little notification.
 
             // only play this when we don't use built in sound, this
             // isn't as consistent
             #[cfg(not(feature = "sound"))]
-            notify.sound_name("alarm-clock-elapsed");
+            notify.sound_name(kind.sound_name());
 
             // The user sets the time with the expectation to be notified, but it's
-            // not like the moon is crashing into the earth
-            notify.urgency(notify_rust::Urgency::Normal);
+            // not like the moon is crashing into the earth, unless they say so
+            notify.urgency(self.config.notify_urgency.into());
 
             // We don't need to have it be displayed for ever, the TUI shows that the time is up
             // (100%) already.
-            notify.timeout(notify_rust::Timeout::Default);
-
-            notify.summary(&format!(
-                "Your countdown of {} is up.",
-                humantime::Duration::from(self.countdown.unwrap())
+            notify.timeout(self.config.notify_timeout.map_or(
+                notify_rust::Timeout::Default,
+                |timeout| {
+                    notify_rust::Timeout::Milliseconds(
+                        u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX),
+                    )
+                },
             ));
+
+            notify.summary(summary);
             // NOTE: this will only work on machines with a proper desktop, not
             // with things like WSL2 or a docker container. Therefore, it is behind
             // the desktop feature.
@@ -407,40 +2384,1206 @@ impl Clock {
         }
         Ok(())
     }
+    /// Run the shell command in the background, its output would mess up the TUI
+    fn exec(command: String) {
+        std::thread::spawn(move || {
+            debug!("running command: {command}");
+            #[cfg(unix)]
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(&command)
+                .stdin(std::process::Stdio::null())
+                .output();
+            #[cfg(not(unix))]
+            let output = std::process::Command::new("cmd")
+                .arg("/C")
+                .arg(&command)
+                .stdin(std::process::Stdio::null())
+                .output();
+            match output {
+                Ok(output) if output.status.success() => debug!("command '{command}' is done"),
+                Ok(output) => error!(
+                    "command '{command}' failed with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(e) => error!("could not run command '{command}': {e}"),
+            }
+        });
+    }
+    /// Whether the terminal bell should ring, it is left out with [`no_beep`](ClockConfig::no_beep)
+    /// and would end up in the JSON
+    #[must_use]
+    pub const fn rings_bell(&self) -> bool {
+        !self.config.json && !self.config.no_beep
+    }
     fn beep() -> anyhow::Result<()> {
         print!("\x07");
         std::io::stdout().flush()?;
         Ok(())
     }
-    fn partition(r: Rect) -> HashMap<&'static str, Rect> {
+    /// Split the inner area of the clock into the areas of its widgets
+    ///
+    /// The areas are `clockw` for the big clock, `datew` for the date, `timebarw` and
+    /// `timebarw_label` for the timebar, `utcw` below the clock for the time in UTC, which is
+    /// only a line high `with_utc`, and `lapsw` below that for the laps. The big clock gets
+    /// enough space for its `pixel_size`. `tzw` and `moonw` are the left and right half of the
+    /// last line, if the [timezone](Self::shows_zone) or the [moon](ClockConfig::moon) is shown.
+    /// The [compact](ClockConfig::compact) clock is a single
+    /// line above the timebars, the date is part of it and `datew` only centers the timebar.
+    /// With only the [bar](ClockConfig::bar_only), the timebars get all the space and there is
+    /// neither a clock nor a date.
+    ///
+    /// `timebars` is the area of all timebars, it is high enough for the
+    /// [stacked timebars](Self::stacked_bars), see [`partition_stacked`](Self::partition_stacked).
+    /// `sparklinew` below the timebar is only there with the [`sparkline`](ClockConfig::sparkline).
+    #[must_use]
+    pub fn partition(&self, r: Rect, pixel_size: PixelSize) -> HashMap<&'static str, Rect> {
+        let stacked = self.stacked_bars().len();
+        let sparkline_height = if self.config.sparkline {
+            SPARKLINE_HEIGHT
+        } else {
+            0
+        };
+        let mut constraints = [
+            Constraint::Length(if stacked > 0 {
+                u16::try_from(stacked * 2 + 1).unwrap_or(u16::MAX)
+            } else {
+                3 + sparkline_height
+            }),
+            Constraint::Length(if self.config.bar_only {
+                0
+            } else if self.config.compact {
+                2
+            } else if self.config.ascii {
+                ui::ASCII_HEIGHT + 1
+            } else {
+                ui::clock_height(pixel_size)
+            }),
+            Constraint::Length(u16::from(self.config.with_utc)),
+            Constraint::Min(0),
+            Constraint::Length(u16::from(self.config.moon || self.shows_zone())),
+        ];
+        // the compact clock is a header above the timebars
+        if self.config.compact {
+            constraints.swap(0, 1);
+        }
+        if self.config.bar_only {
+            (constraints[0], constraints[3]) = (Constraint::Fill(1), Constraint::Length(0));
+        }
         let part = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Length(if r.width > 80 { 8 } else { 5 }),
-            ])
+            .constraints(constraints)
             .split(r);
+        let (timebars_area, clock_area) = if self.config.compact {
+            (part[1], part[0])
+        } else {
+            (part[0], part[1])
+        };
         #[allow(clippy::cast_sign_loss)]
         #[allow(clippy::cast_possible_truncation)]
-        let hlen_date: u16 = (f32::from(part[1].width) * 0.32) as u16;
+        let hlen_date: u16 = if self.config.bar_only {
+            0
+        } else if self.config.compact {
+            // there is no date, but as much space as the timebar leaves on its right centers it
+            let padding = if r.width > 80 { 0.43 } else { 0.25 };
+            (f32::from(clock_area.width) * padding / (1.0 + padding)) as u16
+        } else {
+            (f32::from(clock_area.width) * 0.32) as u16
+        };
         let subparts = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
                 Constraint::Length(hlen_date),
-                Constraint::Length(part[0].width - hlen_date),
+                Constraint::Length(timebars_area.width - hlen_date),
             ])
-            .split(part[0]);
+            .split(timebars_area);
+
+        let corners = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(part[4]);
 
         let timebarw_spaces = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(1), Constraint::Length(1)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(sparkline_height),
+            ])
             .split(subparts[1]);
 
         HashMap::from([
-            ("clockw", part[1]),
+            ("clockw", clock_area),
             ("timebarw", timebarw_spaces[0]),
             ("timebarw_label", timebarw_spaces[1]),
+            ("sparklinew", timebarw_spaces[2]),
+            ("timebars", subparts[1]),
             ("datew", subparts[0]),
+            ("utcw", part[2]),
+            ("lapsw", part[3]),
+            ("tzw", corners[0]),
+            ("moonw", corners[1]),
         ])
     }
+    /// Split the area of the timebars into the gauge and the label below it for each of the
+    /// `stacked` timebars
+    #[must_use]
+    pub fn partition_stacked(r: Rect, stacked: usize) -> Vec<(Rect, Rect)> {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); stacked * 2])
+            .split(r);
+        rows.chunks_exact(2).map(|bar| (bar[0], bar[1])).collect()
+    }
+    /// Split the inner area of the world clock into one row for each of the `zones`
+    ///
+    /// Each row has the areas `labelw` for the name and date and `timew` for the time. With
+    /// `big`, the time gets enough space for [`PixelSize::Quadrant`](tui_big_text::PixelSize::Quadrant).
+    #[must_use]
+    pub fn partition_world(r: Rect, zones: usize, big: bool) -> Vec<HashMap<&'static str, Rect>> {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(if big { 5 } else { 2 }); zones])
+            .split(r);
+        rows.iter()
+            .map(|row| {
+                let cols = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(32), Constraint::Percentage(68)])
+                    .split(*row);
+                HashMap::from([("labelw", cols[0]), ("timew", cols[1])])
+            })
+            .collect()
+    }
+    /// Split the inner area of the [chess clock](ClockConfig::chess) into a column per player
+    ///
+    /// Each column has the areas `labelw` for the name of the player and `timew` for the time,
+    /// in the middle of the column. With `big`, the time gets enough space for
+    /// [`PixelSize::Quadrant`](tui_big_text::PixelSize::Quadrant).
+    #[must_use]
+    pub fn partition_chess(r: Rect, big: bool) -> Vec<HashMap<&'static str, Rect>> {
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(r);
+        cols.iter()
+            .map(|col| {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Fill(1),
+                        Constraint::Length(2),
+                        Constraint::Length(if big { 5 } else { 1 }),
+                        Constraint::Fill(1),
+                    ])
+                    .split(*col);
+                HashMap::from([("labelw", rows[1]), ("timew", rows[2])])
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn parse_locale(s: &str) -> Result<Locale, String> {
+    Locale::try_from(s).map_err(|_| format!("unknown locale: '{s}', try something like 'de_DE'"))
+}
+
+fn parse_timezone(s: &str) -> Result<Tz, String> {
+    s.parse()
+        .map_err(|_| format!("unknown timezone: '{s}', try something like 'Europe/Berlin'"))
+}
+
+fn parse_alarm(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|e| format!("could not parse the alarm time '{s}', use HH:MM[:SS]: {e}"))
+}
+
+/// Parse a duration like humantime does, or like a clock shows it, "90:00" or "1:30:00"
+///
+/// The first number can be as large as it wants, "90:00" is 90 minutes. The others are minutes
+/// and seconds, so they are below 60.
+pub(crate) fn parse_timebar_duration(s: &str) -> Result<std::time::Duration, String> {
+    if !s.contains(':') {
+        return humantime::parse_duration(s).map_err(|e| format!("invalid duration '{s}': {e}"));
+    }
+    let invalid = || format!("invalid duration '{s}', use MM:SS or HH:MM:SS");
+    let parts = s
+        .split(':')
+        .map(|part| part.trim().parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (first, rest) = match parts.as_slice() {
+        [first, rest @ ..] if (1..=2).contains(&rest.len()) => (first, rest),
+        _ => return Err(invalid()),
+    };
+    if rest.iter().any(|n| *n >= 60) {
+        return Err(invalid());
+    }
+    let secs = rest
+        .iter()
+        .try_fold(*first, |secs, n| secs.checked_mul(60)?.checked_add(*n))
+        .ok_or_else(invalid)?;
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+fn parse_until(s: &str) -> Result<DateTime<Local>, String> {
+    const FORMATS: &[&str] = &[
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ];
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Local));
+    }
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| format!("could not parse the date '{s}', use YYYY-MM-DD[THH:MM[:SS]]"))?
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| format!("the time '{s}' does not exist in the local timezone"))
+}
+
+/// What a notification is about, each kind can have [its own sound](Clock::sound_for)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyKind {
+    /// the timebar is up
+    Done,
+    /// the work of the pomodoro is done, the break starts
+    WorkOver,
+    /// the break of the pomodoro is over
+    BreakOver,
+    /// a milestone of [notify at](ClockConfig::notify_at) was passed
+    Milestone,
+}
+
+impl NotifyKind {
+    /// The name of the sound the desktop plays for this kind of notification, see the
+    /// [FreeDesktop Sound Naming Specification](http://0pointer.de/public/sound-naming-spec.html)
+    #[must_use]
+    pub const fn sound_name(self) -> &'static str {
+        match self {
+            Self::Done | Self::WorkOver => "alarm-clock-elapsed",
+            Self::BreakOver | Self::Milestone => "complete",
+        }
+    }
+}
+
+/// How urgent the desktop notification is, see [`notify_rust::Urgency`]
+#[cfg(feature = "desktop")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+#[cfg(feature = "desktop")]
+impl From<Urgency> for notify_rust::Urgency {
+    fn from(urgency: Urgency) -> Self {
+        match urgency {
+            Urgency::Low => Self::Low,
+            Urgency::Normal => Self::Normal,
+            Urgency::Critical => Self::Critical,
+        }
+    }
+}
+
+#[cfg(feature = "sound")]
+fn parse_volume(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(volume) if volume.is_finite() => Ok(volume),
+        _ => Err(format!(
+            "invalid volume: '{s}', use a number from 0.0 to 1.0"
+        )),
+    }
+}
+
+/// Format a duration that can't be negative, like "1m 2s"
+pub(crate) fn format_duration(duration: chrono::Duration) -> humantime::Duration {
+    humantime::Duration::from(duration.to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{DateTime, Datelike, Local, Timelike};
+    use libpt::cli::clap::Parser;
+
+    use super::timebar::TimeBarLength;
+    use super::timesource::{FixedTime, SteppableTime};
+    use super::ui::Data;
+    use super::{Clock, ClockConfig, NotifyKind};
+
+    /// A clock with the command line `args`
+    pub(super) fn parse<I, T>(args: I) -> Clock
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        Clock::new(ClockConfig::parse_from(args))
+    }
+
+    /// A clock with the command line `args`, for which it is always `now`
+    pub(super) fn parse_at<I, T>(args: I, now: DateTime<Local>) -> Clock
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let mut c = parse(args);
+        c.set_time_source(Arc::new(FixedTime(now)));
+        c
+    }
+
+    /// The time `s` in UTC, like "2024-07-20 13:37:30"
+    pub(super) fn utc(s: &str) -> DateTime<Local> {
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+            .with_timezone(&Local)
+    }
+
+    /// The time the tests run at, so that they don't depend on the wall clock
+    pub(super) fn fixed_now() -> DateTime<Local> {
+        utc("2024-07-20 13:37:30")
+    }
+
+    /// A clock with the command line `args` that does not beep, play sounds or print anything
+    ///
+    /// Everything that notifies is quiet with json.
+    fn quiet<I, T>(args: I) -> Clock
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let args = args.into_iter().map(Into::<std::ffi::OsString>::into);
+        #[allow(unused_mut)] // only muted with the sound
+        let mut c = parse(args.chain(["--json".into()]));
+        #[cfg(feature = "sound")]
+        {
+            c.config.sound = false;
+        }
+        c
+    }
+
+    #[test]
+    fn no_timebar_before_the_setup() {
+        let mut c = parse(["some exec", "-u", "5m"]);
+        let now = fixed_now();
+        assert_eq!(c.timebar_ratio(now), None);
+        assert_eq!(c.timebar_remaining(now), None);
+        c.maybe_reset_since_zero(now);
+        c.snooze(now);
+        let _ = c.next_wakeup(now);
+        assert_eq!(c.last_reset, None);
+        assert_eq!(c.schedule(), None);
+        let mut c = parse(["some exec", "-t"]);
+        assert_eq!(c.timer_elapsed(now), None);
+        c.lap(now);
+        assert!(c.laps().is_empty());
+    }
+
+    #[test]
+    fn the_minute_starts_without_an_offset() {
+        let mut c = parse_at(["some exec", "-m"], fixed_now());
+        c.reset();
+        let start = c.last_reset.unwrap();
+        assert_eq!((start.second(), start.nanosecond()), (0, 0));
+        assert_eq!(c.timebar_ratio(start), Some(0.0));
+        assert_eq!(
+            c.timebar_ratio(start + chrono::Duration::seconds(59)),
+            Some(59.0 / 60.0)
+        );
+    }
+
+    #[test]
+    fn the_time_can_be_mocked() {
+        let mut c = parse(["some exec", "--hour", "--mock-now", "2024-06-10T14:59:30"]);
+        c.setup().unwrap();
+        let now = c.now();
+        assert_eq!((now.hour(), now.minute(), now.second()), (14, 59, 30));
+        assert_eq!(c.last_reset.unwrap().hour(), 14);
+        assert!(c.timebar_ratio(now).unwrap() >= 59.5 / 60.0);
+    }
+
+    #[test]
+    fn the_paused_ratio_stays() {
+        let now = fixed_now();
+        let mut c = parse_at(["some exec", "-c", "1m"], now);
+        c.last_reset = Some(now.with_second(0).unwrap());
+
+        c.toggle_pause(now.with_second(10).unwrap());
+        assert_eq!(
+            c.timebar_ratio(now.with_second(40).unwrap()),
+            c.timebar_ratio(now.with_second(10).unwrap())
+        );
+        // and does not jump once resumed
+        c.toggle_pause(now.with_second(40).unwrap());
+        assert_eq!(
+            c.timebar_ratio(now.with_second(50).unwrap()),
+            Some(20.0 / 60.0)
+        );
+
+        c.reset();
+        assert_eq!(c.timebar_ratio(c.last_reset.unwrap()), Some(0.0));
+        assert!(!c.paused);
+    }
+
+    #[test]
+    fn timer_laps() {
+        let mut c = parse(["some exec", "-t"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        c.lap(start + chrono::Duration::seconds(10));
+        c.lap(start + chrono::Duration::seconds(25));
+        assert_eq!(
+            c.laps(),
+            [chrono::Duration::seconds(10), chrono::Duration::seconds(15)]
+        );
+        assert_eq!(
+            c.current_lap(start + chrono::Duration::seconds(30)),
+            Some(chrono::Duration::seconds(5))
+        );
+        assert_eq!(
+            c.stopwatch_time(start + chrono::Duration::milliseconds(62_345)),
+            "01:02.345"
+        );
+        assert_eq!(
+            c.stopwatch_time(start + chrono::Duration::milliseconds(3_723_004)),
+            "1:02:03.004"
+        );
+    }
+
+    #[test]
+    fn the_stopwatch_only_counts_while_it_runs() {
+        let mut c = parse_at(["some exec", "-t", "--stopped"], fixed_now());
+        assert!(c.render_once(80, 10).unwrap().contains("(stopped)"));
+        let start = c.last_reset.unwrap();
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        assert_eq!(c.timer_elapsed(at(10)), Some(chrono::Duration::zero()));
+        c.toggle_pause(at(10));
+        c.toggle_pause(at(15));
+        c.toggle_pause(at(20));
+        assert_eq!(c.timer_elapsed(at(30)), Some(chrono::Duration::seconds(15)));
+        c.reset();
+        assert!(c.paused);
+    }
+
+    #[test]
+    fn snoozing_adds_to_the_countdown() {
+        let mut c = parse(["some exec", "-u", "1m", "--snooze", "5m"]);
+        let now = fixed_now();
+        c.last_reset = Some(now);
+        // not before it went off
+        c.snooze(now + chrono::Duration::seconds(30));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Countup(60)));
+        assert_eq!(
+            c.timebar_remaining(now + chrono::Duration::seconds(23)),
+            Some(37)
+        );
+        assert_eq!(
+            c.timebar_remaining(now + chrono::Duration::seconds(61)),
+            Some(0)
+        );
+        c.did_notify = true;
+        c.snooze(now + chrono::Duration::seconds(90));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Countup(90 + 300)));
+        assert!(!c.did_notify);
+        // snoozing again adds to the snooze
+        c.snooze(now + chrono::Duration::seconds(100));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Countup(90 + 600)));
+        assert_eq!(
+            c.timebar_ratio(now + chrono::Duration::seconds(345)),
+            Some(0.5)
+        );
+        c.reset();
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Countup(60)));
+    }
+
+    #[test]
+    fn the_hour_starts_on_the_hour() {
+        let mut c = parse_at(["some exec", "-o"], fixed_now());
+        c.reset();
+        let start = c.in_timezone(c.last_reset.unwrap());
+        assert_eq!(
+            (start.minute(), start.second(), start.nanosecond()),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn the_custom_timebar_resets_on_exact_multiples() {
+        let mut c = quiet(["some exec", "-c", "5s"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        for period in 1..=3 {
+            let boundary = start + chrono::Duration::seconds(5 * period);
+            c.maybe_reset_since_zero(boundary - chrono::Duration::milliseconds(10));
+            assert_eq!(c.last_reset, Some(boundary - chrono::Duration::seconds(5)));
+            c.maybe_reset_since_zero(boundary + chrono::Duration::milliseconds(700));
+            assert_eq!(c.last_reset, Some(boundary));
+        }
+        c.maybe_reset_since_zero(start + chrono::Duration::seconds(32));
+        assert_eq!(c.last_reset, Some(start + chrono::Duration::seconds(30)));
+        assert_eq!(c.notify_summary(), "Another 5s have passed.");
+    }
+
+    #[test]
+    fn the_pomodoro_phases_start_on_schedule() {
+        let mut c = quiet(["some exec", "--pomodoro"]);
+        c.last_reset = Some(fixed_now());
+        let schedule = c.schedule().unwrap();
+        // the ticks come a little late, but the phases don't drift
+        for (at, _) in &schedule[1..4] {
+            c.maybe_reset_since_zero(*at + chrono::Duration::milliseconds(700));
+            assert_eq!(c.last_reset, Some(*at));
+        }
+        // a pause moves the next phase back by as long as it took
+        c.paused_total = chrono::Duration::seconds(10);
+        let (at, _) = &schedule[4];
+        c.maybe_reset_since_zero(*at + chrono::Duration::milliseconds(10_700));
+        assert_eq!(c.last_reset, Some(*at + chrono::Duration::seconds(10)));
+    }
+
+    #[test]
+    fn the_notification_says_which_timebar_is_done() {
+        let c = parse(["some exec", "-u", "1m", "--snooze", "5m"]);
+        assert_eq!(c.notify_summary(), "Your countdown of 1m is up.");
+    }
+
+    #[cfg(feature = "desktop")]
+    #[test]
+    fn the_desktop_notification_can_be_configured() {
+        use super::Urgency;
+        let c = parse([
+            "some exec",
+            "--notify-urgency",
+            "critical",
+            "--notify-timeout",
+            "10s",
+        ]);
+        assert_eq!(c.config.notify_urgency, Urgency::Critical);
+        assert_eq!(
+            c.config.notify_timeout,
+            Some(std::time::Duration::from_secs(10))
+        );
+        let c = parse(["some exec"]);
+        assert_eq!(c.config.notify_urgency, Urgency::Normal);
+        assert_eq!(c.config.notify_timeout, None);
+
+        let c = parse([
+            "some exec",
+            "-u",
+            "25m",
+            "--notify-message",
+            "Tea is ready after {duration}!",
+        ]);
+        assert_eq!(c.notify_summary(), "Tea is ready after 25m!");
+    }
+
+    #[test]
+    fn wake_up_when_the_custom_timebar_is_full() {
+        let mut c = parse(["some exec", "-c", "1m"]);
+        let now = fixed_now().with_nanosecond(200_000_000).unwrap();
+        c.last_reset = Some(now - chrono::Duration::milliseconds(59_900));
+        assert_eq!(c.next_wakeup(now), std::time::Duration::from_millis(100));
+        // or else on the next second
+        c.last_reset = Some(now);
+        assert_eq!(c.next_wakeup(now), std::time::Duration::from_millis(800));
+    }
+
+    #[test]
+    fn alarms() {
+        let mut c = parse(["some exec", "--alarm", "12:00"]);
+        let now = fixed_now().with_second(0).unwrap();
+        c.last_reset = Some(now.with_hour(11).unwrap().with_minute(0).unwrap());
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Alarm(60 * 60)));
+        // the alarm rolls over to tomorrow
+        c.last_reset = Some(now.with_hour(13).unwrap().with_minute(0).unwrap());
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Alarm(23 * 60 * 60)));
+
+        let mut c = parse([
+            "some exec",
+            "--alarm",
+            "15:30",
+            "--alarm",
+            "08:00",
+            "--alarm",
+            "12:00",
+        ]);
+        c.sort_alarms(chrono::NaiveTime::from_hms_opt(11, 0, 0).unwrap());
+        assert_eq!(
+            c.config.alarm,
+            ["12:00:00", "15:30:00", "08:00:00"].map(|t| t.parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn count_down_to_a_date() {
+        let now = fixed_now();
+        let mut c = parse_at(["some exec", "--until", "2000-01-01"], now);
+        c.setup().unwrap();
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Until(0)));
+        assert_eq!(c.timebar_ratio(now), Some(1.0));
+        assert!(c.is_finished());
+
+        let mut c = parse(["some exec", "--until", "2999-12-25T00:00"]);
+        c.config.until = Some(now + chrono::Duration::days(2));
+        c.last_reset = Some(now);
+        assert_eq!(
+            c.timebar_len(),
+            Some(TimeBarLength::Until(2 * 24 * 60 * 60))
+        );
+        assert_eq!(c.timebar_ratio(now + chrono::Duration::days(1)), Some(0.5));
+    }
+
+    #[test]
+    fn count_down_to_the_new_year() {
+        let mut c = parse_at(["some exec", "--new-year"], fixed_now());
+        c.setup().unwrap();
+        let until = c.config.until.unwrap();
+        assert_eq!((until.month(), until.day(), until.hour()), (1, 1, 0));
+        assert_eq!(until.year(), 2025);
+        assert!(matches!(c.timebar_len(), Some(TimeBarLength::Until(_))));
+        assert!(c.notify_summary().starts_with("Happy new year"));
+    }
+
+    #[test]
+    fn the_eta_is_when_the_timebar_is_full() {
+        for (args, eta) in [
+            (&["-c", "5m"][..], Some(chrono::Duration::minutes(5))),
+            (&["-t"], None),
+        ] {
+            let mut c = parse_at(std::iter::once(&"some exec").chain(args), fixed_now());
+            c.setup().unwrap();
+            let mut data = Data::new(c.timebar_len());
+            data.update(c.now(), String::new(), String::new(), None);
+            assert_eq!(
+                c.timebar_eta(&data),
+                eta.map(|eta| c.last_reset.unwrap() + eta)
+            );
+        }
+    }
+
+    #[test]
+    fn the_countdown_ends_on_the_exact_second() {
+        let mut c = quiet(["some exec", "-u", "2s"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        let at = |ms| start + chrono::Duration::milliseconds(ms);
+        assert!(!c.reached_end(at(1999)));
+        c.notify_when_done(at(1999));
+        assert!(!c.did_notify);
+        assert!(c.reached_end(at(2000)));
+        c.notify_when_done(at(2000));
+        assert!(c.did_notify);
+        c.snooze(at(2500));
+        assert!(!c.reached_end(at(2500)));
+    }
+
+    #[test]
+    fn parse_durations_like_a_clock_shows_them() {
+        for (s, secs) in [
+            ("90:00", 5400),
+            ("1:30:00", 5400),
+            ("0:05", 5),
+            ("1h30m", 5400),
+        ] {
+            let c = parse(["some exec", "-u", s]);
+            assert_eq!(c.config.countdown.map(|d| d.as_secs()), Some(secs), "{s}");
+        }
+        for s in [
+            "1:60",
+            "1:2:3:4",
+            ":30",
+            "a:30",
+            "1h:30",
+            "999999999999999999:00:00",
+        ] {
+            assert!(
+                ClockConfig::try_parse_from(["some exec", "-c", s]).is_err(),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn months_have_their_own_length() {
+        let mut c = parse(["some exec", "--month", "--tz", "UTC"]);
+        let leap_day = utc("2024-02-29 12:00:00");
+        c.last_reset = Some(c.calendar_start(TimeBarLength::Month(0), leap_day));
+        assert_eq!(c.last_reset, Some(utc("2024-02-01 00:00:00")));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Month(29 * 86400)));
+        c.maybe_reset_since_zero(utc("2024-02-29 23:59:00"));
+        assert_eq!(c.last_reset, Some(utc("2024-02-01 00:00:00")));
+        c.maybe_reset_since_zero(utc("2024-03-01 00:00:00"));
+        assert_eq!(c.last_reset, Some(utc("2024-03-01 00:00:00")));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Month(31 * 86400)));
+        c.last_reset = Some(utc("2023-02-01 00:00:00"));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Month(28 * 86400)));
+    }
+
+    #[test]
+    fn leap_years_have_one_more_day() {
+        let mut c = parse(["some exec", "--year", "--tz", "UTC"]);
+        let leap_day = utc("2024-02-29 12:00:00");
+        c.last_reset = Some(c.calendar_start(TimeBarLength::Year(0), leap_day));
+        assert_eq!(c.last_reset, Some(utc("2024-01-01 00:00:00")));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Year(366 * 86400)));
+        assert_eq!(c.timebar_ratio(utc("2024-07-02 00:00:00")), Some(0.5));
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        let c = parse(["some exec", "--week", "--tz", "UTC"]);
+        assert_eq!(
+            c.calendar_start(TimeBarLength::Week, utc("2024-02-29 12:00:00")),
+            utc("2024-02-26 00:00:00")
+        );
+    }
+
+    #[test]
+    fn stacked_timebars_follow_the_wall_clock() {
+        let c = parse(["some exec", "--all-bars", "--tz", "UTC"]);
+        let noon = utc("2024-02-29 12:30:00");
+        assert_eq!(
+            c.stacked_bars()
+                .into_iter()
+                .map(|len| c.wall_ratio(len, noon))
+                .collect::<Vec<_>>(),
+            [0.0, 0.5, 12.5 / 24.0]
+        );
+    }
+
+    #[test]
+    fn the_date_can_have_more_in_it() {
+        let noon = utc("2024-06-10 12:00:00");
+        let c = parse(["some exec", "--extended-date", "--tz", "UTC"]);
+        assert_eq!(c.fdate(c.in_timezone(noon)), "2024-06-10 (W24, day 162)");
+
+        // the names in another language
+        let c = parse(["some exec", "--locale", "de_DE", "--date-format", "%A %B"]);
+        assert_eq!(c.fdate(c.in_timezone(noon)), "Montag Juni");
+        assert!(ClockConfig::try_parse_from(["some exec", "--locale", "xx_YY"]).is_err());
+    }
+
+    #[test]
+    fn chime_as_many_times_as_the_hour() {
+        let mut c = quiet(["some exec", "--chime", "--chime-count", "--tz", "UTC"]);
+        let three = utc("2024-07-20 15:00:00");
+        // not when starting
+        c.maybe_chime(three - chrono::Duration::seconds(1));
+        assert_eq!(c.chimes_left, 0);
+        c.maybe_chime(three);
+        assert_eq!(c.chimes_left, 2);
+        c.maybe_chime(three + chrono::Duration::milliseconds(100));
+        assert_eq!(c.chimes_left, 2);
+        c.maybe_chime(three + chrono::Duration::seconds(1));
+        c.maybe_chime(three + chrono::Duration::seconds(2));
+        c.maybe_chime(three + chrono::Duration::seconds(3));
+        assert_eq!(c.chimes_left, 0);
+    }
+
+    #[test]
+    fn click_on_the_timebar_and_the_clock() {
+        use ratatui::crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        use ratatui::layout::Rect;
+
+        use super::keys::Action;
+        use super::ui::ClockWidget;
+
+        let mut c = parse_at(["some exec", "-c", "1m", "--mouse"], fixed_now());
+        c.setup().unwrap();
+        let mut data = Data::new(c.timebar_len());
+        data.update(
+            fixed_now(),
+            "date".to_owned(),
+            "12:00:00".to_owned(),
+            Some(0.0),
+        );
+        let area = Rect::new(0, 0, 100, 30);
+        let areas = ClockWidget::new(&c, &data).areas(area);
+        let click = |rect: Rect| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: rect.x,
+            row: rect.y,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(
+            c.mouse_action(click(areas["timebarw"]), area, &data),
+            Some(Action::Reset)
+        );
+        assert_eq!(
+            c.mouse_action(click(areas["clockw"]), area, &data),
+            Some(Action::Pause)
+        );
+        assert_eq!(c.mouse_action(click(Rect::default()), area, &data), None);
+    }
+
+    #[test]
+    fn the_seconds_can_be_hidden() {
+        let mut c = parse(["some exec", "-T"]);
+        assert_eq!(c.time_format(), "%I:%M:%S %p");
+        c.show_seconds = false;
+        assert_eq!(c.time_format(), "%I:%M %p");
+    }
+
+    #[cfg(feature = "sound")]
+    #[test]
+    fn the_volume_is_clamped() {
+        let mut c = parse_at(["some exec", "--volume", "3"], fixed_now());
+        c.setup().unwrap();
+        assert!((c.config.volume - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn walk_through_the_steps_of_the_intervals() {
+        let mut c = quiet(["some exec", "--intervals", "work:40s,rest:20s x2"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Interval(40)));
+        c.notify_when_done(at(40));
+        assert_eq!(c.notify_summary(), "work is over, now rest.");
+        c.maybe_reset_since_zero(at(40));
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Interval(20)));
+        assert_eq!(c.last_reset, Some(at(40)));
+        assert!(!c.is_finished());
+        for end in [60, 100, 120] {
+            c.notify_when_done(at(end));
+            c.maybe_reset_since_zero(at(end));
+        }
+        assert_eq!(
+            c.interval_step().map(|step| (step.name, step.round)),
+            Some(("rest", 2))
+        );
+        assert!(c.is_finished());
+        assert_eq!(c.notify_summary(), "All intervals are done.");
+    }
+
+    #[test]
+    fn notify_once_for_each_milestone() {
+        let mut c = quiet(["some exec", "-c", "100s", "--notify-at", "25,50,75"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        c.maybe_notify_milestones(at(0));
+        assert_eq!(c.milestones, 0);
+        c.maybe_notify_milestones(at(30));
+        assert_eq!(c.milestones, 1 << 25);
+        c.maybe_notify_milestones(at(40));
+        assert_eq!(c.milestones, 1 << 25);
+        c.maybe_notify_milestones(at(80));
+        assert_eq!(c.milestones, 1 << 25 | 1 << 50 | 1 << 75);
+        c.last_reset = Some(at(100));
+        c.maybe_notify_milestones(at(100));
+        assert_eq!(c.milestones, 0);
+    }
+
+    #[test]
+    fn prewarn_in_the_last_seconds_but_not_at_zero() {
+        let mut c = quiet(["some exec", "-u", "10s", "--prewarn", "3s"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        let mut warned = Vec::new();
+        for ms in (0..=10_000).step_by(250) {
+            c.maybe_prewarn(start + chrono::Duration::milliseconds(ms));
+            if c.last_prewarn != warned.last().copied() {
+                warned.extend(c.last_prewarn);
+            }
+        }
+        assert_eq!(warned, [3, 2, 1]);
+    }
+
+    #[test]
+    fn leave_out_the_terminal_bell() {
+        assert!(parse(["some exec", "-u", "10s"]).rings_bell());
+        assert!(!parse(["some exec", "-u", "10s", "--no-beep"]).rings_bell());
+        // the chime still goes off on the hour, just without the bell
+        let mut c = parse(["some exec", "--no-beep", "--chime", "--tz", "UTC"]);
+        assert!(!c.rings_bell());
+        c.maybe_chime(utc("2024-07-20 13:59:59"));
+        c.maybe_chime(utc("2024-07-20 14:00:00"));
+        assert_eq!(c.last_chime, Some(utc("2024-07-20 14:00:00")));
+    }
+
+    #[test]
+    fn each_kind_of_notification_has_its_own_sound() {
+        let mut c = parse(["some exec", "--pomodoro"]);
+        assert_eq!(c.notify_kind(), NotifyKind::BreakOver);
+        c.pomodoro_on_break = true;
+        assert_eq!(c.notify_kind(), NotifyKind::WorkOver);
+        assert_eq!(
+            parse(["some exec", "-u", "1m"]).notify_kind(),
+            NotifyKind::Done
+        );
+        #[cfg(feature = "sound")]
+        {
+            use super::sound::{BundledSound, Sound};
+
+            let c = parse(["some exec", "-u", "1m"]);
+            assert_eq!(
+                c.sound_for(NotifyKind::Done),
+                Sound::Bundled(BundledSound::Alarm)
+            );
+            assert_eq!(
+                c.sound_for(NotifyKind::Milestone),
+                Sound::Bundled(BundledSound::Notify)
+            );
+            let c = parse([
+                "some exec",
+                "-u",
+                "1m",
+                "--sound-file",
+                "gong.ogg",
+                "--sound-milestone",
+                "alarm",
+            ]);
+            assert_eq!(
+                c.sound_for(NotifyKind::Done),
+                Sound::File("gong.ogg".into())
+            );
+            assert_eq!(
+                c.sound_for(NotifyKind::Milestone),
+                Sound::Bundled(BundledSound::Alarm)
+            );
+            assert!(ClockConfig::try_parse_from(["some exec", "--sound-done", ""]).is_err());
+        }
+    }
+
+    #[test]
+    fn the_big_countdown_shows_the_time_left() {
+        let start = fixed_now();
+        let secs = |s| start + chrono::Duration::seconds(s);
+        let mut c = parse(["some exec", "-u", "5m", "--big-countdown"]);
+        assert_eq!(c.countdown_time(start), None);
+        c.last_reset = Some(start);
+        assert_eq!(c.countdown_time(start).as_deref(), Some("05:00"));
+        assert_eq!(c.countdown_time(secs(90)).as_deref(), Some("03:30"));
+        assert_eq!(c.countdown_time(secs(400)).as_deref(), Some("00:00"));
+        let mut c = parse(["some exec", "-u", "2h", "--big-countdown"]);
+        c.last_reset = Some(start);
+        assert_eq!(c.countdown_time(secs(1)).as_deref(), Some("1:59:59"));
+        let mut c = parse(["some exec", "--timer", "--big-countdown"]);
+        c.last_reset = Some(start);
+        assert_eq!(c.countdown_time(secs(61)).as_deref(), Some("01:01"));
+        let mut c = parse(["some exec", "-u", "5m"]);
+        c.last_reset = Some(start);
+        assert_eq!(c.countdown_time(start), None);
+        assert!(ClockConfig::try_parse_from(["some exec", "--big-countdown"]).is_err());
+    }
+
+    #[test]
+    fn redraw_every_half_second_while_blinking() {
+        let mut c = parse(["some exec", "-u", "2s", "--bar-only"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        let ms = |ms| start + chrono::Duration::milliseconds(ms);
+        assert!(!c.timebar_blinks(ms(1900)));
+        assert_eq!(
+            c.next_wakeup(ms(1900)),
+            std::time::Duration::from_millis(100)
+        );
+        assert!(c.timebar_blinks(ms(2100)));
+        assert_eq!(
+            c.next_wakeup(ms(2100)),
+            std::time::Duration::from_millis(400)
+        );
+        assert_eq!(
+            c.next_wakeup(ms(2600)),
+            std::time::Duration::from_millis(400)
+        );
+    }
+
+    #[test]
+    fn the_phase_shifts_the_minute() {
+        let now = fixed_now();
+        let mut c = parse_at(["some exec", "-m", "--phase", "30s"], now);
+        c.setup().unwrap();
+        let start = c.last_reset.unwrap();
+        assert_eq!(start.second(), 30);
+        assert!(now.signed_duration_since(start) < chrono::Duration::minutes(1));
+        c.maybe_reset_since_zero(start + chrono::Duration::seconds(59));
+        assert_eq!(c.last_reset, Some(start));
+        c.maybe_reset_since_zero(start + chrono::Duration::seconds(60));
+        assert_eq!(c.last_reset, Some(start + chrono::Duration::seconds(60)));
+
+        // but not by a whole timebar
+        assert!(parse_at(["some exec", "-m", "--phase", "1m"], now)
+            .setup()
+            .is_err());
+        assert!(parse_at(["some exec", "--hour", "--phase", "59:59"], now)
+            .setup()
+            .is_ok());
+        assert!(parse_at(["some exec", "-u", "5m", "--phase", "1s"], now)
+            .setup()
+            .is_err());
+    }
+
+    #[test]
+    fn the_window_title_shows_what_is_left() {
+        for (args, title) in [
+            (&["-u", "5m"][..], "4m 30s left - crock"),
+            (&["-u", "5m", "--title", "tea"], "4m 30s left - tea"),
+            (&["-m"], "13:37:00 - crock"),
+        ] {
+            let mut c = parse_at(std::iter::once(&"some exec").chain(args), fixed_now());
+            c.setup().unwrap();
+            let now = c.last_reset.unwrap() + chrono::Duration::seconds(30);
+            let mut data = Data::new(c.timebar_len());
+            data.update(now, String::new(), "13:37:00".to_owned(), None);
+            assert_eq!(c.window_title(&data), title);
+        }
+    }
+
+    #[test]
+    fn the_smooth_timebar_redraws_between_the_seconds() {
+        let start = fixed_now();
+        let half = start + chrono::Duration::milliseconds(500);
+        let mut c = parse_at(["some exec", "-c", "2s", "--smooth-bar"], start);
+        c.setup().unwrap();
+        assert_eq!(c.next_wakeup(half), std::time::Duration::from_millis(33));
+        let mut data = Data::new(c.timebar_len());
+        data.update(start, String::new(), "13:37:00".to_owned(), Some(0.0));
+        data.update(half, String::new(), "13:37:00".to_owned(), Some(0.25));
+        assert!(!data.changed());
+        data.set_smooth_bar(true);
+        assert!(data.changed());
+    }
+
+    #[test]
+    fn the_custom_timebar_can_be_shorter_than_a_second() {
+        let mut c = quiet(["some exec", "-c", "1500ms", "--smooth-bar"]);
+        let start = fixed_now();
+        c.last_reset = Some(start);
+        let ms = |ms| start + chrono::Duration::milliseconds(ms);
+        assert_eq!(c.timebar_len(), Some(TimeBarLength::Custom(1500)));
+        assert_eq!(c.timebar_ratio(ms(750)), Some(0.5));
+        c.maybe_reset_since_zero(ms(1600));
+        assert_eq!(c.last_reset, Some(ms(1500)));
+        assert_eq!(c.notify_summary(), "Another 1s 500ms have passed.");
+    }
+
+    #[test]
+    fn the_dry_run_knows_when_things_go_off() {
+        let mut c = parse_at(
+            ["some exec", "--intervals", "work:40s,rest:20s x2"],
+            fixed_now(),
+        );
+        c.setup().unwrap();
+        let start = c.last_reset.unwrap();
+        let schedule = c.schedule().unwrap();
+        assert_eq!(schedule.len(), 5);
+        assert_eq!(
+            schedule[1],
+            (
+                start + chrono::Duration::seconds(40),
+                "rest 1/2 starts (20s)".to_owned()
+            )
+        );
+        assert_eq!(
+            schedule[4],
+            (
+                start + chrono::Duration::minutes(2),
+                "all intervals are done".to_owned()
+            )
+        );
+
+        let mut c = parse_at(["some exec", "--pomodoro"], fixed_now());
+        c.setup().unwrap();
+        assert_eq!(c.schedule().unwrap().len(), 8);
+        assert_eq!(
+            c.schedule().unwrap()[3].0,
+            c.last_reset.unwrap() + chrono::Duration::minutes(55)
+        );
+
+        let mut c = parse_at(["some exec", "-m"], fixed_now());
+        c.setup().unwrap();
+        assert!(c.schedule().unwrap().is_empty());
+    }
+
+    #[test]
+    fn the_digits_follow_the_palette() {
+        use ratatui::style::Color;
+
+        let c = parse(["some exec", "--palette", "deuteranopia", "--gradient"]);
+        assert_ne!(c.digit_color(), Color::Red);
+        // the chess clock has colors of its own
+        let mut c = parse_at(["some exec", "--no-color", "--chess", "1m"], fixed_now());
+        c.setup().unwrap();
+        assert_eq!(c.digit_color(), Color::Reset);
+    }
+
+    /// A time source that starts at 14:59 and is moved on by the test
+    fn stepped() -> (DateTime<Local>, Arc<SteppableTime>) {
+        let start = utc("2024-07-20 14:59:00");
+        (start, Arc::new(SteppableTime::new(start)))
+    }
+
+    #[test]
+    fn the_countdown_is_done_when_the_time_source_says_so() {
+        let (_, time) = stepped();
+        let mut c = quiet(["some exec", "-u", "3s"]);
+        c.set_time_source(time.clone());
+        c.setup().unwrap();
+        time.advance(chrono::Duration::milliseconds(2999));
+        c.notify_when_done(c.now());
+        assert!(!c.did_notify && !c.is_finished());
+        time.advance(chrono::Duration::milliseconds(1));
+        c.notify_when_done(c.now());
+        assert!(c.did_notify && c.is_finished());
+        assert_eq!(c.timebar_ratio(c.now()), Some(1.0));
+    }
+
+    #[test]
+    fn the_custom_timebar_resets_on_the_stepped_time() {
+        let (start, time) = stepped();
+        let mut c = quiet(["some exec", "-c", "10s"]);
+        c.set_time_source(time.clone());
+        c.setup().unwrap();
+        time.advance(chrono::Duration::seconds(10));
+        c.maybe_reset_since_zero(c.now());
+        assert_eq!(c.last_reset, Some(start + chrono::Duration::seconds(10)));
+        assert_eq!(c.timebar_ratio(c.now()), Some(0.0));
+        time.advance(chrono::Duration::seconds(25));
+        c.maybe_reset_since_zero(c.now());
+        assert_eq!(c.last_reset, Some(start + chrono::Duration::seconds(30)));
+    }
+
+    #[test]
+    fn the_label_goes_on_with_the_next_hour_right_at_the_boundary() {
+        use ratatui::buffer::Buffer;
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Widget;
+
+        use super::ui::{buffer_to_string, ClockWidget};
+
+        let (start, time) = stepped();
+        time.set(start + chrono::Duration::seconds(59));
+        let mut c = parse(["some exec", "-o", "--tz", "UTC"]);
+        c.set_time_source(time.clone());
+        c.setup().unwrap();
+        let area = Rect::new(0, 0, 80, 10);
+        let mut label = || {
+            c.maybe_reset_since_zero(c.now());
+            let mut data = Data::new(c.timebar_len());
+            let now = c.now();
+            data.update(now, String::new(), String::new(), c.timebar_ratio(now));
+            let mut buf = Buffer::empty(area);
+            ClockWidget::new(&c, &data).render(area, &mut buf);
+            buffer_to_string(&buf)
+        };
+        let frame = label();
+        assert!(frame.contains("59m 59s / 1h | 14:00 -> 15:00"), "{frame}");
+        time.advance(chrono::Duration::seconds(1));
+        let frame = label();
+        assert!(frame.contains("0s / 1h | 15:00 -> 16:00"), "{frame}");
+    }
 }
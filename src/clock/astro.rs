@@ -0,0 +1,107 @@
+//! What the sky looks like, only from the date
+
+use chrono::{DateTime, TimeZone, Utc};
+
+/// How long it takes the moon to go from one new moon to the next, in days
+pub const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+/// The phase of the moon, in the order it goes through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    const ALL: [Self; 8] = [
+        Self::New,
+        Self::WaxingCrescent,
+        Self::FirstQuarter,
+        Self::WaxingGibbous,
+        Self::Full,
+        Self::WaningGibbous,
+        Self::LastQuarter,
+        Self::WaningCrescent,
+    ];
+
+    /// The phase of the moon at `time`
+    ///
+    /// This counts synodic months since a known new moon, which is off by less than a day.
+    ///
+    /// # Panics
+    ///
+    /// The known new moon is not a valid date, which it is.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // milliseconds since 2000 fit well enough
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // at most 8
+    pub fn at<T: TimeZone>(time: &DateTime<T>) -> Self {
+        // the new moon of 2000-01-06 18:14 UTC
+        let known_new_moon = Utc
+            .with_ymd_and_hms(2000, 1, 6, 18, 14, 0)
+            .single()
+            .expect("the known new moon is not a valid date");
+        let days = time
+            .with_timezone(&Utc)
+            .signed_duration_since(known_new_moon)
+            .num_milliseconds() as f64
+            / 86_400_000.0;
+        let age = days.rem_euclid(SYNODIC_MONTH) / SYNODIC_MONTH;
+        // each phase is centered on its moment, the new moon is from -1/16 to 1/16
+        Self::ALL[(age * 8.0).round() as usize % 8]
+    }
+
+    /// The name of the phase, like "waxing crescent"
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::New => "new moon",
+            Self::WaxingCrescent => "waxing crescent",
+            Self::FirstQuarter => "first quarter",
+            Self::WaxingGibbous => "waxing gibbous",
+            Self::Full => "full moon",
+            Self::WaningGibbous => "waning gibbous",
+            Self::LastQuarter => "last quarter",
+            Self::WaningCrescent => "waning crescent",
+        }
+    }
+
+    /// The emoji of the phase, like "🌒"
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::New => "🌑",
+            Self::WaxingCrescent => "🌒",
+            Self::FirstQuarter => "🌓",
+            Self::WaxingGibbous => "🌔",
+            Self::Full => "🌕",
+            Self::WaningGibbous => "🌖",
+            Self::LastQuarter => "🌗",
+            Self::WaningCrescent => "🌘",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_phases_of_the_moon() {
+        // the solar eclipse and the full moon after it
+        for (time, phase) in [
+            ("2024-04-08T18:21:00Z", MoonPhase::New),
+            ("2024-04-15T19:13:00Z", MoonPhase::FirstQuarter),
+            ("2024-04-23T23:49:00Z", MoonPhase::Full),
+            ("2024-05-01T11:27:00Z", MoonPhase::LastQuarter),
+        ] {
+            let time = chrono::DateTime::parse_from_rfc3339(time).unwrap();
+            assert_eq!(MoonPhase::at(&time), phase, "{time}");
+        }
+    }
+}
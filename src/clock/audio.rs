@@ -0,0 +1,59 @@
+//! Finding the audio output to play the sounds on
+
+use anyhow::Context;
+use libpt::log::{debug, warn};
+use rodio::cpal::traits::HostTrait;
+use rodio::{DeviceTrait, OutputStream, OutputStreamHandle};
+
+/// The names of all audio outputs, for picking one with `--audio-device`
+///
+/// # Errors
+///
+/// The audio outputs could not be listed.
+pub fn output_devices() -> anyhow::Result<Vec<String>> {
+    Ok(rodio::cpal::default_host()
+        .output_devices()
+        .context("could not list the audio outputs")?
+        .filter_map(|device| device.name().ok())
+        .collect())
+}
+
+/// Open the audio output called `name`, or the default one if there is no such output
+///
+/// A name that is not exactly the name of an output is also found in the names of the outputs,
+/// ignoring case, so "hdmi" finds "HDMI 0 (Speakers)".
+///
+/// # Errors
+///
+/// The audio output could not be opened.
+pub fn open(name: Option<&str>) -> anyhow::Result<(OutputStream, OutputStreamHandle)> {
+    let host = rodio::cpal::default_host();
+    if let Some(name) = name {
+        let devices: Vec<_> = host
+            .output_devices()
+            .context("could not list the audio outputs")?
+            .filter_map(|device| Some((device.name().ok()?, device)))
+            .collect();
+        let found = devices
+            .iter()
+            .find(|(device_name, _)| device_name == name)
+            .or_else(|| {
+                devices.iter().find(|(device_name, _)| {
+                    device_name.to_lowercase().contains(&name.to_lowercase())
+                })
+            });
+        if let Some((device_name, device)) = found {
+            debug!("playing on the audio output '{device_name}'");
+            return OutputStream::try_from_device(device)
+                .with_context(|| format!("could not open the audio output '{device_name}'"));
+        }
+        warn!("there is no audio output '{name}', using the default one");
+    }
+    debug!(
+        "playing on the default audio output '{}'",
+        host.default_output_device()
+            .and_then(|device| device.name().ok())
+            .unwrap_or_default()
+    );
+    OutputStream::try_default().context("could not open the audio output")
+}
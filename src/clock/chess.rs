@@ -0,0 +1,151 @@
+//! Two countdowns for board games, only the one of the player whose turn it is runs
+
+use chrono::{DateTime, Duration, Local};
+
+/// The clocks of both players of the [chess mode](super::ClockConfig::chess)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChessState {
+    /// how much time each player has left, without the running turn
+    remaining: [Duration; 2],
+    /// whose clock is running, or runs once the game starts
+    active: usize,
+    /// when the running turn started, `None` before the game started and after it ended
+    turn_started: Option<DateTime<Local>>,
+    /// the player who ran out of time
+    loser: Option<usize>,
+}
+
+impl ChessState {
+    /// Both players have `time`, the game starts with the first [switch](Self::switch)
+    #[must_use]
+    pub const fn new(time: Duration) -> Self {
+        Self {
+            remaining: [time, time],
+            active: 0,
+            turn_started: None,
+            loser: None,
+        }
+    }
+
+    /// The player whose turn it is, 0 or 1
+    #[must_use]
+    pub const fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The player who ran out of time, if the game is over
+    #[must_use]
+    pub const fn loser(&self) -> Option<usize> {
+        self.loser
+    }
+
+    /// Is the clock of the [active](Self::active) player running?
+    #[must_use]
+    pub const fn is_running(&self) -> bool {
+        self.turn_started.is_some()
+    }
+
+    /// How much time `player` has left at `current_time`, never negative
+    #[must_use]
+    pub fn remaining(&self, player: usize, current_time: DateTime<Local>) -> Duration {
+        let mut remaining = self.remaining[player];
+        if let Some(turn_started) = self.turn_started.filter(|_| player == self.active) {
+            remaining -= current_time.signed_duration_since(turn_started);
+        }
+        remaining.max(Duration::zero())
+    }
+
+    /// Start the game, or end the turn and start the clock of the other player
+    ///
+    /// Nothing happens once somebody ran out of time.
+    pub fn switch(&mut self, current_time: DateTime<Local>) {
+        if self.loser.is_some() {
+            return;
+        }
+        if self.turn_started.is_some() {
+            self.remaining[self.active] = self.remaining(self.active, current_time);
+            self.active = 1 - self.active;
+        }
+        self.turn_started = Some(current_time);
+    }
+
+    /// Stop the game if the active player ran out of time at `current_time`
+    ///
+    /// Returns true only the first time, so that we notify once.
+    pub fn flag(&mut self, current_time: DateTime<Local>) -> bool {
+        if !self.is_running() || self.remaining(self.active, current_time) > Duration::zero() {
+            return false;
+        }
+        self.remaining[self.active] = Duration::zero();
+        self.turn_started = None;
+        self.loser = Some(self.active);
+        true
+    }
+
+    /// How long until the shown time of the running clock changes
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)] // the remainder of a positive duration
+    pub fn until_next_second(&self, current_time: DateTime<Local>) -> Option<std::time::Duration> {
+        if !self.is_running() {
+            return None;
+        }
+        let millis = self.remaining(self.active, current_time).num_milliseconds() % 1000;
+        Some(std::time::Duration::from_millis(if millis == 0 {
+            1000
+        } else {
+            millis as u64
+        }))
+    }
+}
+
+/// Show the remaining time of a player like "4:59" or "1:02:03"
+///
+/// Started seconds count as whole ones, so "0:00" is only shown when the time is up.
+#[must_use]
+pub fn format_remaining(remaining: Duration) -> String {
+    let millis = remaining.num_milliseconds().max(0);
+    let secs = (millis + 999) / 1000;
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::tests::fixed_now;
+
+    #[test]
+    fn only_the_clock_of_the_player_whose_turn_it_is_runs() {
+        let start = fixed_now();
+        let at = |secs| start + Duration::seconds(secs);
+        let mut chess = ChessState::new(Duration::minutes(5));
+        assert!(!chess.is_running());
+        assert_eq!(chess.remaining(0, at(10)), Duration::minutes(5));
+        chess.switch(start);
+        assert_eq!(chess.remaining(0, at(10)), Duration::seconds(290));
+        assert_eq!(chess.remaining(1, at(10)), Duration::minutes(5));
+        chess.switch(at(10));
+        assert_eq!(chess.active(), 1);
+        assert_eq!(chess.remaining(0, at(100)), Duration::seconds(290));
+        assert_eq!(chess.remaining(1, at(100)), Duration::seconds(210));
+
+        // the flag falls once and ends the game
+        assert!(!chess.flag(at(309)));
+        assert!(chess.flag(at(310)));
+        assert!(!chess.flag(at(311)));
+        assert_eq!(chess.loser(), Some(1));
+        chess.switch(at(320));
+        assert_eq!(chess.remaining(0, at(400)), Duration::seconds(290));
+    }
+
+    #[test]
+    fn format_the_remaining_time() {
+        assert_eq!(format_remaining(Duration::minutes(5)), "5:00");
+        assert_eq!(format_remaining(Duration::milliseconds(1)), "0:01");
+        assert_eq!(format_remaining(Duration::seconds(3723)), "1:02:03");
+    }
+}
@@ -0,0 +1,438 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use libpt::cli::clap::parser::ValueSource;
+use libpt::cli::clap::{ArgMatches, ValueEnum};
+use libpt::log::debug;
+use serde::Deserialize;
+
+use super::history::HistoryFormat;
+use super::keys::parse_key;
+#[cfg(feature = "sound")]
+use super::sound::Sound;
+use super::ui::{BarStyle, Font, Palette};
+use super::{
+    parse_alarm, parse_locale, parse_timebar_duration, parse_timezone, parse_until, ClockConfig,
+    TIMEBAR_ARGS,
+};
+
+/// Options read from the config file
+///
+/// The keys are the same as the long command line options, for example:
+///
+/// ```toml
+/// countdown = "25m"
+/// twelve-hour = true
+/// sound = false
+///
+/// [keys]
+/// quit = ["q", "ctrl-c"]
+///
+/// [sounds]
+/// milestone = "notify"
+/// done = "~/sounds/gong.ogg"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigFile {
+    timer: Option<bool>,
+    ms: Option<bool>,
+    minute: Option<bool>,
+    day: Option<bool>,
+    hour: Option<bool>,
+    week: Option<bool>,
+    month: Option<bool>,
+    year: Option<bool>,
+    custom: Option<String>,
+    countdown: Option<String>,
+    alarm: Option<Vec<String>>,
+    until: Option<String>,
+    since: Option<String>,
+    new_year: Option<bool>,
+    chess: Option<String>,
+    show_remaining: Option<bool>,
+    big_countdown: Option<bool>,
+    fuzzy: Option<bool>,
+    warn_threshold: Option<String>,
+    percent: Option<bool>,
+    overtime: Option<bool>,
+    repeat: Option<bool>,
+    snooze: Option<String>,
+    pomodoro: Option<bool>,
+    pomodoro_work: Option<String>,
+    pomodoro_break: Option<String>,
+    intervals: Option<String>,
+    twelve_hour: Option<bool>,
+    time_format: Option<String>,
+    date_format: Option<String>,
+    extended_date: Option<bool>,
+    locale: Option<String>,
+    tz: Option<String>,
+    world: Option<Vec<String>>,
+    all_bars: Option<bool>,
+    sparkline: Option<bool>,
+    no_border: Option<bool>,
+    padding: Option<u16>,
+    title: Option<String>,
+    set_title: Option<bool>,
+    no_version: Option<bool>,
+    with_utc: Option<bool>,
+    moon: Option<bool>,
+    ntp: Option<String>,
+    binary: Option<bool>,
+    font: Option<String>,
+    ascii: Option<bool>,
+    blink: Option<bool>,
+    no_leading_zero: Option<bool>,
+    compact: Option<bool>,
+    bar_only: Option<bool>,
+    stopped: Option<bool>,
+    show_eta: Option<bool>,
+    bar_style: Option<String>,
+    drain: Option<bool>,
+    smooth_bar: Option<bool>,
+    phase: Option<String>,
+    tick_rate: Option<String>,
+    color: Option<String>,
+    palette: Option<String>,
+    no_color: Option<bool>,
+    gradient: Option<bool>,
+    exit_on_finish: Option<bool>,
+    exit_delay: Option<String>,
+    exec: Option<String>,
+    notify_at: Option<Vec<u8>>,
+    prewarn: Option<String>,
+    show_tz: Option<bool>,
+    log_history: Option<String>,
+    chime: Option<bool>,
+    mouse: Option<bool>,
+    chime_count: Option<bool>,
+    flash: Option<bool>,
+    no_beep: Option<bool>,
+    // accepted even without the feature, so that the config file works for every build
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    sound: Option<bool>,
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    volume: Option<f32>,
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    sound_file: Option<PathBuf>,
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    tick_sound: Option<bool>,
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    audio_device: Option<String>,
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+    notify_urgency: Option<String>,
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+    notify_timeout: Option<String>,
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+    notify_message: Option<String>,
+    keys: Option<KeysConfig>,
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    sounds: Option<SoundsConfig>,
+}
+
+/// The `[keys]` table of the config file, the keys for each action like `--key-quit`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct KeysConfig {
+    quit: Option<Vec<String>>,
+    seconds: Option<Vec<String>>,
+    lap: Option<Vec<String>>,
+    snooze: Option<Vec<String>>,
+    pause: Option<Vec<String>>,
+    reset: Option<Vec<String>>,
+    switch: Option<Vec<String>>,
+}
+
+/// The `[sounds]` table of the config file, the sound for each kind of notification like
+/// `--sound-milestone`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+pub struct SoundsConfig {
+    done: Option<String>,
+    work_over: Option<String>,
+    break_over: Option<String>,
+    milestone: Option<String>,
+}
+
+impl ConfigFile {
+    /// Where the config file is, `~/.config/crock/config.toml` on linux
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+    }
+
+    /// Read the config file
+    ///
+    /// A missing config file is not an error, we just don't have one then.
+    ///
+    /// # Errors
+    ///
+    /// * The config file exists but could not be read
+    /// * The config file is not valid
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        let Some(path) = Self::path() else {
+            debug!("no config directory, not loading a config file");
+            return Ok(None);
+        };
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("no config file at {}", path.display());
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("could not read config file {}", path.display()))
+            }
+        };
+        let config = toml::from_str(&raw)
+            .with_context(|| format!("could not parse config file {}", path.display()))?;
+        debug!("loaded config file {}: {config:#?}", path.display());
+        Ok(Some(config))
+    }
+
+    /// Fill in the options of `config` that were not given on the command line
+    ///
+    /// # Errors
+    ///
+    /// A value in the config file could not be parsed.
+    #[allow(clippy::too_many_lines)] // one line per option, splitting that up does not help
+    pub fn apply(self, config: &mut ClockConfig, matches: &ArgMatches) -> anyhow::Result<()> {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        let parse_duration = |s: String| {
+            humantime::parse_duration(&s).with_context(|| format!("invalid duration: '{s}'"))
+        };
+
+        macro_rules! merge {
+            ($field:ident) => {
+                merge!($field, Ok::<_, anyhow::Error>)
+            };
+            ($field:ident, $parse:expr) => {
+                if let Some(value) = self.$field {
+                    if !from_cli(stringify!($field)) {
+                        config.$field = $parse(value)?;
+                    }
+                }
+            };
+        }
+
+        // the timebars exclude each other, one given on the command line replaces the config
+        if !TIMEBAR_ARGS.iter().any(|id| from_cli(id)) {
+            merge!(timer);
+            merge!(minute);
+            merge!(day);
+            merge!(hour);
+            merge!(week);
+            merge!(month);
+            merge!(year);
+            merge!(pomodoro);
+            merge!(custom, |s: String| parse_timebar_duration(&s)
+                .map(Some)
+                .map_err(anyhow::Error::msg));
+            merge!(countdown, |s: String| parse_timebar_duration(&s)
+                .map(Some)
+                .map_err(anyhow::Error::msg));
+            merge!(chess, |s: String| parse_timebar_duration(&s)
+                .map(Some)
+                .map_err(anyhow::Error::msg));
+            merge!(intervals, |s: String| s
+                .parse()
+                .map(Some)
+                .map_err(anyhow::Error::msg));
+            merge!(alarm, |alarms: Vec<String>| alarms
+                .iter()
+                .map(|s| parse_alarm(s).map_err(anyhow::Error::msg))
+                .collect::<anyhow::Result<_>>());
+            merge!(until, |s: String| parse_until(&s)
+                .map(Some)
+                .map_err(anyhow::Error::msg));
+            merge!(since, |s: String| parse_until(&s)
+                .map(Some)
+                .map_err(anyhow::Error::msg));
+            merge!(new_year);
+        }
+        merge!(ms);
+        merge!(show_remaining);
+        merge!(big_countdown);
+        merge!(fuzzy);
+        merge!(warn_threshold, |s| parse_duration(s).map(Some));
+        merge!(percent);
+        merge!(overtime);
+        merge!(repeat);
+        merge!(snooze, parse_duration);
+        merge!(prewarn, |s| parse_duration(s).map(Some));
+        merge!(pomodoro_work, parse_duration);
+        merge!(pomodoro_break, parse_duration);
+        merge!(twelve_hour);
+        merge!(time_format, |s| Ok::<_, anyhow::Error>(Some(s)));
+        merge!(date_format, |s| Ok::<_, anyhow::Error>(Some(s)));
+        merge!(extended_date);
+        merge!(locale, |s: String| parse_locale(&s)
+            .map(Some)
+            .map_err(anyhow::Error::msg));
+        merge!(binary);
+        merge!(ascii);
+        merge!(font, |s: String| Font::from_str(&s, true)
+            .map(Some)
+            .map_err(anyhow::Error::msg));
+        merge!(blink);
+        merge!(no_leading_zero);
+        merge!(compact);
+        merge!(bar_only);
+        merge!(stopped);
+        merge!(show_eta);
+        merge!(bar_style, |s: String| BarStyle::from_str(&s, true)
+            .map_err(anyhow::Error::msg));
+        merge!(drain);
+        merge!(smooth_bar);
+        merge!(phase, |s: String| parse_timebar_duration(&s)
+            .map(Some)
+            .map_err(anyhow::Error::msg));
+        merge!(tick_rate, parse_duration);
+        merge!(color, |s| Ok::<_, anyhow::Error>(Some(s)));
+        merge!(palette, |s: String| Palette::from_str(&s, true)
+            .map_err(anyhow::Error::msg));
+        merge!(no_color);
+        merge!(gradient);
+        merge!(exit_on_finish);
+        merge!(exit_delay, parse_duration);
+        merge!(exec, |s| Ok::<_, anyhow::Error>(Some(s)));
+        merge!(notify_at, |percents: Vec<u8>| {
+            if let Some(percent) = percents.iter().find(|percent| !(1..100).contains(*percent)) {
+                anyhow::bail!("notify-at must be between 1 and 99, not {percent}");
+            }
+            Ok(percents)
+        });
+        merge!(log_history, |s: String| HistoryFormat::from_str(&s, true)
+            .map(Some)
+            .map_err(anyhow::Error::msg));
+        merge!(show_tz);
+        merge!(chime);
+        merge!(mouse);
+        merge!(chime_count);
+        merge!(flash);
+        merge!(no_beep);
+        merge!(all_bars);
+        merge!(sparkline);
+        merge!(no_border);
+        merge!(padding, |n| Ok::<_, anyhow::Error>(Some(n)));
+        merge!(title, |s| Ok::<_, anyhow::Error>(Some(s)));
+        merge!(set_title);
+        merge!(no_version);
+        merge!(with_utc);
+        merge!(moon);
+        merge!(ntp, |s| Ok::<_, anyhow::Error>(Some(s)));
+        // same for the timezones
+        if !from_cli("timezone") && !from_cli("world") {
+            if let Some(tz) = self.tz {
+                config.timezone = Some(parse_timezone(&tz).map_err(anyhow::Error::msg)?);
+            }
+            merge!(world, |zones: Vec<String>| zones
+                .iter()
+                .map(|s| parse_timezone(s).map_err(anyhow::Error::msg))
+                .collect::<anyhow::Result<_>>());
+        }
+        if let Some(keys) = self.keys {
+            keys.apply(config, from_cli)?;
+        }
+        #[cfg(feature = "desktop")]
+        merge!(notify_urgency, |s: String| super::Urgency::from_str(
+            &s, true
+        )
+        .map_err(anyhow::Error::msg));
+        #[cfg(feature = "desktop")]
+        merge!(notify_timeout, |s| parse_duration(s).map(Some));
+        #[cfg(feature = "desktop")]
+        merge!(notify_message, |s| Ok::<_, anyhow::Error>(Some(s)));
+        #[cfg(feature = "sound")]
+        merge!(sound);
+        #[cfg(feature = "sound")]
+        merge!(volume);
+        #[cfg(feature = "sound")]
+        merge!(tick_sound);
+        #[cfg(feature = "sound")]
+        merge!(audio_device, |s| Ok::<_, anyhow::Error>(Some(s)));
+        #[cfg(feature = "sound")]
+        merge!(sound_file, |path| Ok::<_, anyhow::Error>(Some(
+            expand_home(path)
+        )));
+        #[cfg(feature = "sound")]
+        if let Some(sounds) = self.sounds {
+            sounds.apply(config, from_cli)?;
+        }
+        Ok(())
+    }
+}
+
+impl KeysConfig {
+    /// Fill in the keys of `config` that were not given on the command line
+    fn apply(
+        self,
+        config: &mut ClockConfig,
+        from_cli: impl Fn(&str) -> bool,
+    ) -> anyhow::Result<()> {
+        let parse_keys = |keys: Vec<String>| {
+            keys.iter()
+                .map(|s| parse_key(s).map_err(anyhow::Error::msg))
+                .collect::<anyhow::Result<_>>()
+        };
+        macro_rules! merge_keys {
+            ($action:ident, $field:ident) => {
+                if let Some(value) = self.$action {
+                    if !from_cli(stringify!($field)) {
+                        config.$field = parse_keys(value)?;
+                    }
+                }
+            };
+        }
+        merge_keys!(quit, key_quit);
+        merge_keys!(seconds, key_seconds);
+        merge_keys!(lap, key_lap);
+        merge_keys!(snooze, key_snooze);
+        merge_keys!(pause, key_pause);
+        merge_keys!(reset, key_reset);
+        merge_keys!(switch, key_switch);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sound")]
+impl SoundsConfig {
+    /// Fill in the sounds of `config` that were not given on the command line
+    fn apply(
+        self,
+        config: &mut ClockConfig,
+        from_cli: impl Fn(&str) -> bool,
+    ) -> anyhow::Result<()> {
+        let parse_sound = |s: String| match s.parse().map_err(anyhow::Error::msg)? {
+            Sound::File(path) => Ok::<_, anyhow::Error>(Some(Sound::File(expand_home(path)))),
+            sound @ Sound::Bundled(_) => Ok(Some(sound)),
+        };
+
+        macro_rules! merge_sound {
+            ($kind:ident, $field:ident) => {
+                if let Some(value) = self.$kind {
+                    if !from_cli(stringify!($field)) {
+                        config.$field = parse_sound(value)?;
+                    }
+                }
+            };
+        }
+        merge_sound!(done, sound_done);
+        merge_sound!(work_over, sound_work_over);
+        merge_sound!(break_over, sound_break_over);
+        merge_sound!(milestone, sound_milestone);
+        Ok(())
+    }
+}
+
+/// The shell does not expand the home directory in the config file, so we do it ourselves
+#[cfg(feature = "sound")]
+fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path,
+    }
+}
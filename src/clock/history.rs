@@ -0,0 +1,178 @@
+//! A log of the finished countdowns and timers, for keeping track of what the time went into
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local, SubsecRound};
+use libpt::cli::clap::{self, ValueEnum};
+use libpt::log::debug;
+use serde::Serialize;
+
+use super::timebar::TimeBarLength;
+use super::Clock;
+
+/// How the [history](super::ClockConfig::log_history) is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum HistoryFormat {
+    /// one JSON object per line
+    #[default]
+    Jsonl,
+    /// comma separated values, with a header at the top
+    Csv,
+}
+
+/// One finished countdown or timer in the history
+///
+/// ```json
+/// {"started_at":"2024-07-20T13:37:00+02:00","duration_secs":1500,"label":"work"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Record {
+    /// when it started, not counting the pauses
+    pub started_at: DateTime<Local>,
+    pub duration_secs: u64,
+    /// the [title](super::ClockConfig::title) or what kind of timebar it was
+    pub label: String,
+}
+
+impl Record {
+    /// Where the history is written, `~/.local/share/crock/history.log` on linux
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("history.log"))
+    }
+
+    /// The timebar of `clock` that was just [finished](Clock::finished_duration) at `ended_at`
+    #[must_use]
+    pub fn finished(clock: &Clock, ended_at: DateTime<Local>) -> Option<Self> {
+        let duration = clock.finished_duration()?;
+        Some(Self {
+            started_at: ended_at.trunc_subsecs(0)
+                - chrono::Duration::from_std(duration).unwrap_or_default(),
+            duration_secs: duration.as_secs(),
+            label: Self::label(clock)?,
+        })
+    }
+
+    /// The [timer](super::ClockConfig::timer) of `clock`, stopped at `ended_at`
+    ///
+    /// The timer never finishes by itself, so it is logged when crock quits.
+    #[must_use]
+    pub fn stopwatch(clock: &Clock, ended_at: DateTime<Local>) -> Option<Self> {
+        if clock.timebar_len() != Some(TimeBarLength::Timer) {
+            return None;
+        }
+        Some(Self {
+            started_at: clock.last_reset?,
+            duration_secs: clock
+                .timer_elapsed(ended_at)?
+                .num_seconds()
+                .max(0)
+                .unsigned_abs(),
+            label: Self::label(clock)?,
+        })
+    }
+
+    /// The title of the clock, or else what was counted down
+    fn label(clock: &Clock) -> Option<String> {
+        if let Some(title) = &clock.config.title {
+            return Some(title.clone());
+        }
+        if clock.chess.is_some() {
+            return Some("chess".to_owned());
+        }
+        let label = match clock.timebar_len()? {
+            TimeBarLength::Timer => "timer",
+            TimeBarLength::Countup(_) => "countdown",
+            TimeBarLength::Custom(_) => "custom",
+            TimeBarLength::Alarm(_) => "alarm",
+            TimeBarLength::Until(_) => "until",
+            // it already switched to the next phase
+            TimeBarLength::Pomodoro { .. } if clock.pomodoro_on_break => "work",
+            TimeBarLength::Pomodoro { .. } => "break",
+            TimeBarLength::Interval(_) => return clock.interval_step().map(|s| s.name.to_owned()),
+            _ => return None,
+        };
+        Some(label.to_owned())
+    }
+
+    /// The record as one line of the history, without the newline
+    ///
+    /// # Errors
+    ///
+    /// The record could not be serialized as JSON.
+    pub fn line(&self, format: HistoryFormat) -> anyhow::Result<String> {
+        Ok(match format {
+            HistoryFormat::Jsonl => serde_json::to_string(self)?,
+            HistoryFormat::Csv => format!(
+                "{},{},\"{}\"",
+                self.started_at.to_rfc3339(),
+                self.duration_secs,
+                self.label.replace('"', "\"\"")
+            ),
+        })
+    }
+
+    /// Add the record to the end of the history at its [path](Self::path)
+    ///
+    /// # Errors
+    ///
+    /// There is no data directory, or the history could not be written.
+    pub fn append(&self, format: HistoryFormat) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("no data directory for the history"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("could not open the history in {}", path.display()))?;
+        let mut lines = String::new();
+        if format == HistoryFormat::Csv && file.metadata()?.len() == 0 {
+            lines.push_str("started_at,duration_secs,label\n");
+        }
+        lines.push_str(&self.line(format)?);
+        lines.push('\n');
+        file.write_all(lines.as_bytes())
+            .with_context(|| format!("could not write the history to {}", path.display()))?;
+        debug!("logged {self:?} to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::tests::{fixed_now, parse};
+
+    #[test]
+    fn the_history_has_when_and_how_long_the_timebars_ran() {
+        let start = fixed_now();
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        let c = parse([
+            "some exec",
+            "-c",
+            "25m",
+            "--title",
+            "Deep \"work\"",
+            "--log-history",
+        ]);
+        assert_eq!(c.config.log_history, Some(HistoryFormat::Jsonl));
+        let record = Record::finished(&c, at(1500)).unwrap();
+        assert_eq!(record.started_at, start);
+        assert_eq!(record.duration_secs, 1500);
+        assert_eq!(
+            record.line(HistoryFormat::Csv).unwrap(),
+            format!("{},1500,\"Deep \"\"work\"\"\"", start.to_rfc3339())
+        );
+
+        let mut c = parse(["some exec", "--timer", "--log-history=csv"]);
+        assert_eq!(c.config.log_history, Some(HistoryFormat::Csv));
+        c.last_reset = Some(start);
+        let record = Record::stopwatch(&c, at(90)).unwrap();
+        assert_eq!((record.duration_secs, record.label.as_str()), (90, "timer"));
+    }
+}
@@ -0,0 +1,128 @@
+//! A plan of named steps that are counted down one after another, like for interval training
+
+use std::str::FromStr;
+
+use super::parse_timebar_duration;
+
+/// The steps of the [intervals](super::ClockConfig::intervals) and how often they are repeated
+///
+/// ```
+/// use crock::clock::intervals::Intervals;
+///
+/// let intervals: Intervals = "work:40s,rest:20s x8".parse().unwrap();
+/// assert_eq!(intervals.len(), 16);
+/// assert_eq!(intervals.step(2).unwrap().name, "work");
+/// assert_eq!(intervals.step(2).unwrap().round, 2);
+/// assert!(intervals.step(16).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Intervals {
+    /// the name and length of each step of one round
+    pub steps: Vec<(String, std::time::Duration)>,
+    /// how many rounds there are, at least one
+    pub rounds: u32,
+}
+
+/// Where in the [`Intervals`] we are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step<'a> {
+    pub name: &'a str,
+    pub len: std::time::Duration,
+    /// the round this step is in, counted from 1
+    pub round: u32,
+}
+
+impl Intervals {
+    /// How many steps there are in all rounds together
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.steps.len() * self.rounds as usize
+    }
+
+    /// Are there no steps at all? Parsing never gives such intervals
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The step at `idx`, counting the steps of all rounds together
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // there are never more rounds than a u32 holds
+    pub fn step(&self, idx: usize) -> Option<Step<'_>> {
+        if idx >= self.len() {
+            return None;
+        }
+        let (name, len) = &self.steps[idx % self.steps.len()];
+        Some(Step {
+            name,
+            len: *len,
+            round: (idx / self.steps.len()) as u32 + 1,
+        })
+    }
+}
+
+impl FromStr for Intervals {
+    type Err = String;
+
+    /// Parse steps like "work:40s,rest:20s", optionally repeated with " x8"
+    ///
+    /// The lengths are like [`--custom`](super::ClockConfig::custom), so "warmup:5:00" works too.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (steps, rounds) = match s.trim().rsplit_once(' ') {
+            // only a last word like "x8" is the rounds, a step can be called "xtra"
+            Some((steps, rounds))
+                if rounds.starts_with(['x', 'X'])
+                    && rounds[1..].bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                let rounds = rounds[1..]
+                    .parse()
+                    .ok()
+                    .filter(|rounds| *rounds > 0)
+                    .ok_or_else(|| format!("invalid number of rounds: '{rounds}', like 'x8'"))?;
+                (steps, rounds)
+            }
+            _ => (s, 1),
+        };
+        let steps = steps
+            .split(',')
+            .map(|step| {
+                let (name, len) = step
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid step: '{step}', use NAME:DURATION"))?;
+                if name.trim().is_empty() {
+                    return Err(format!("the step '{step}' needs a name"));
+                }
+                let len = parse_timebar_duration(len.trim())?;
+                if len.as_secs() == 0 {
+                    return Err(format!("the step '{step}' must be at least a second long"));
+                }
+                Ok((name.trim().to_owned(), len))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { steps, rounds })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_the_intervals() {
+        let intervals: Intervals = "warmup:1:00, work:40s".parse().unwrap();
+        assert_eq!(intervals.rounds, 1);
+        let (name, len) = &intervals.steps[0];
+        assert_eq!((name.as_str(), len.as_secs()), ("warmup", 60));
+        assert!("work:0s".parse::<Intervals>().is_err());
+        assert!("work:40s xeight".parse::<Intervals>().is_err());
+        assert!("work:40s x".parse::<Intervals>().is_err());
+        assert!(":40s".parse::<Intervals>().is_err());
+        // a step may start with an x
+        let intervals: Intervals = "warmup:1:00, xtra:40s".parse().unwrap();
+        assert_eq!(intervals.rounds, 1);
+        assert_eq!(intervals.steps[1].0, "xtra");
+        let intervals: Intervals = "warmup:1:00, X-ray:40s x3".parse().unwrap();
+        assert_eq!(intervals.rounds, 3);
+        assert_eq!(intervals.steps[1].0, "X-ray");
+    }
+}
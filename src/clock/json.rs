@@ -0,0 +1,107 @@
+use std::io::Write;
+use std::time::Instant;
+
+use chrono::SubsecRound;
+use libpt::log::debug;
+use serde::Serialize;
+
+use super::ui::Data;
+use super::{Clock, SAVE_INTERVAL};
+
+/// One line of the JSON output
+///
+/// ```json
+/// {"time":"13:37:00","date":"2024-07-20","ratio":0.5,"remaining":30,"paused":false,"finished":false}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Status {
+    pub time: String,
+    pub date: String,
+    /// progress of the timebar, from 0.0 to 1.0
+    pub ratio: Option<f64>,
+    /// seconds until the timebar is full
+    pub remaining: Option<i64>,
+    pub paused: bool,
+    /// the countdown or the last alarm is up
+    pub finished: bool,
+}
+
+impl Status {
+    #[must_use]
+    pub fn new(clock: &Clock, data: &Data) -> Self {
+        Self {
+            time: data.ftime().to_owned(),
+            date: data.fdate().to_owned(),
+            ratio: data
+                .timebar_ratio()
+                .filter(|_| clock.timebar_len().is_some()),
+            remaining: clock.timebar_remaining(*data.now()),
+            paused: clock.paused,
+            finished: clock.is_finished(),
+        }
+    }
+}
+
+/// Print the [`Status`] as JSON each time the shown time changes, without a TUI
+///
+/// Stops when the countdown is finished and
+/// [`exit_on_finish`](super::ClockConfig::exit_on_finish) is set, on SIGINT or SIGTERM, or when
+/// nobody reads our output anymore.
+///
+/// # Errors
+///
+/// * The [setup](Clock::setup) fails
+/// * The [signal handlers](Clock::register_signals) could not be registered
+/// * Writing to stdout fails
+pub fn run(mut clock: Clock) -> anyhow::Result<()> {
+    clock.setup()?;
+    clock.register_signals()?;
+    let mut data = clock.new_data();
+    let result = print_loop(&mut clock, &mut data);
+    clock.save_state(&data);
+    clock.log_stopwatch();
+    result
+}
+
+fn print_loop(clock: &mut Clock, data: &mut Data) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    let mut saved_at = Instant::now();
+    loop {
+        if clock.terminated() {
+            return Ok(());
+        }
+        // the same order as in the TUI, see there
+        clock.maybe_reset_since_zero(clock.now());
+        clock.maybe_chime(clock.now());
+        clock.maybe_notify_milestones(clock.now());
+        clock.maybe_prewarn(clock.now());
+        let now = clock.now().trunc_subsecs(0);
+        let wall_time = clock.in_timezone(now);
+        data.update(
+            now,
+            clock.fdate(wall_time),
+            clock.localized(&wall_time, clock.time_format()),
+            clock.timebar_ratio(now),
+        );
+        if data.changed() {
+            clock.notify_when_done(now);
+            let line = serde_json::to_string(&Status::new(clock, data))?;
+            match writeln!(stdout, "{line}").and_then(|()| stdout.flush()) {
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                    debug!("stdout was closed, exiting");
+                    return Ok(());
+                }
+                result => result?,
+            }
+            if clock.config.exit_on_finish && clock.is_finished() {
+                debug!("the countdown is finished, exiting");
+                return Ok(());
+            }
+        }
+        if saved_at.elapsed() >= SAVE_INTERVAL {
+            clock.save_state(data);
+            saved_at = Instant::now();
+        }
+        std::thread::sleep(clock.next_wakeup(clock.now()));
+    }
+}
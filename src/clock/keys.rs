@@ -0,0 +1,286 @@
+//! Which key does what while the clock is running
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something the user can do with a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    /// show or hide the seconds
+    Seconds,
+    Lap,
+    Snooze,
+    Pause,
+    Reset,
+    /// end the turn in the chess clock
+    Switch,
+}
+
+impl Action {
+    pub const ALL: [Self; 7] = [
+        Self::Quit,
+        Self::Seconds,
+        Self::Lap,
+        Self::Snooze,
+        Self::Pause,
+        Self::Reset,
+        Self::Switch,
+    ];
+
+    /// The keys for this action if none are configured
+    #[must_use]
+    pub fn default_keys(self) -> Vec<Key> {
+        let char = |c| Key::new(KeyCode::Char(c), KeyModifiers::NONE);
+        match self {
+            Self::Quit => vec![
+                char('q'),
+                Key::new(KeyCode::Esc, KeyModifiers::NONE),
+                Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            ],
+            Self::Seconds => vec![char('s')],
+            Self::Lap => vec![char('l')],
+            Self::Snooze => vec![char('z')],
+            Self::Pause => vec![char(' ')],
+            Self::Reset => vec![char('r')],
+            Self::Switch => vec![
+                Key::new(KeyCode::Left, KeyModifiers::NONE),
+                Key::new(KeyCode::Right, KeyModifiers::NONE),
+            ],
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Quit => "quit",
+            Self::Seconds => "seconds",
+            Self::Lap => "lap",
+            Self::Snooze => "snooze",
+            Self::Pause => "pause",
+            Self::Reset => "reset",
+            Self::Switch => "switch",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A key with modifiers, like "q", "esc" or "ctrl-c"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    #[must_use]
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Was this key pressed in `event`?
+    ///
+    /// Shift is already in the character, so it is ignored for those.
+    #[must_use]
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        self.code == event.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // a single character is always the key, even "-"
+        while rest.chars().count() > 1 {
+            let Some((modifier, key)) = rest.split_once('-') else {
+                break;
+            };
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => return Err(format!("unknown modifier '{modifier}' in key '{s}'")),
+            };
+            rest = key;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => match name.strip_prefix('f').map(str::parse) {
+                    Some(Ok(n @ 1..=12)) => KeyCode::F(n),
+                    _ => {
+                        return Err(format!(
+                            "unknown key '{s}', try something like 'q' or 'ctrl-x'"
+                        ))
+                    }
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => write!(f, "{}", format!("{code:?}").to_lowercase()),
+        }
+    }
+}
+
+/// For parsing keys with clap
+pub(crate) fn parse_key(s: &str) -> Result<Key, String> {
+    s.parse()
+}
+
+/// Which keys do which [`Action`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl KeyMap {
+    /// Bind the keys of each action, actions without keys get their
+    /// [default keys](Action::default_keys)
+    ///
+    /// A default key that is bound to another action is left out.
+    ///
+    /// # Errors
+    ///
+    /// A key is bound to two different actions.
+    pub fn new(mut keys: impl FnMut(Action) -> Vec<Key>) -> anyhow::Result<Self> {
+        let mut bindings: Vec<(Key, Action)> = Vec::new();
+        let mut unbound = Vec::new();
+        for action in Action::ALL {
+            let action_keys = keys(action);
+            if action_keys.is_empty() {
+                unbound.push(action);
+            }
+            for key in action_keys {
+                match bindings.iter().find(|(bound, _)| *bound == key) {
+                    Some((_, other)) if *other != action => {
+                        return Err(anyhow!(
+                            "the key '{key}' is bound to both {other} and {action}"
+                        ));
+                    }
+                    Some(_) => (),
+                    None => bindings.push((key, action)),
+                }
+            }
+        }
+        for action in unbound {
+            for key in action.default_keys() {
+                if !bindings.iter().any(|(bound, _)| *bound == key) {
+                    bindings.push((key, action));
+                }
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    /// What to do for the pressed key, if anything
+    #[must_use]
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(key, _)| key.matches(event))
+            .map(|(_, action)| *action)
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::new(|_| Vec::new()).expect("the default keys are bound twice")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn key(s: &str) -> Key {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn default_keys() {
+        let keys = KeyMap::default();
+        assert_eq!(
+            keys.action(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keys.action(&press(KeyCode::Char(' '), KeyModifiers::NONE)),
+            Some(Action::Pause)
+        );
+        assert_eq!(
+            keys.action(&press(KeyCode::Char('x'), KeyModifiers::NONE)),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!(key("ctrl-x").to_string(), "ctrl-x");
+        assert_eq!(key("-").code, KeyCode::Char('-'));
+        assert_eq!(key("F5").code, KeyCode::F(5));
+        assert!("hyper-x".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn keys_can_be_remapped_but_not_bound_twice() {
+        let keys = KeyMap::new(|action| match action {
+            Action::Pause => vec![key("s")],
+            _ => Vec::new(),
+        })
+        .unwrap();
+        assert_eq!(
+            keys.action(&press(KeyCode::Char('s'), KeyModifiers::NONE)),
+            Some(Action::Pause)
+        );
+        assert!(KeyMap::new(|action| match action {
+            Action::Pause | Action::Lap => vec![key("p")],
+            _ => Vec::new(),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! A small SNTP client, for correcting a clock that drifts
+//!
+//! See [RFC 4330](https://www.rfc-editor.org/rfc/rfc4330) for the packet format.
+
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context};
+use libpt::log::{debug, warn};
+
+/// The NTP timestamps count from 1900, unix time from 1970
+const NTP_TO_UNIX_SECS: i64 = 2_208_988_800;
+/// How long to wait for an answer from the server
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// How often to ask the server again, the clock does not drift that fast
+#[allow(clippy::duration_suboptimal_units)] // `from_mins` needs a much newer Rust
+pub const INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// The port of NTP, unless the server says otherwise
+const PORT: u16 = 123;
+
+/// Ask `server` how far off the local clock is
+///
+/// The server is a host name or address, optionally with a port like "localhost:1123". Add the
+/// result to the local time to get the time of the server.
+///
+/// # Errors
+///
+/// * The server could not be reached or did not answer in time
+/// * The answer is not a valid NTP packet
+pub fn query(server: &str) -> anyhow::Result<chrono::Duration> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).context("could not open a socket")?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    socket
+        .connect(address(server))
+        .with_context(|| format!("could not connect to '{server}'"))?;
+
+    let mut packet = [0u8; 48];
+    // no leap second warning, version 3, client mode
+    packet[0] = 0b00_011_011;
+    let sent_at = unix_now();
+    socket.send(&packet)?;
+    let len = socket
+        .recv(&mut packet)
+        .with_context(|| format!("no answer from '{server}'"))?;
+    let received_at = unix_now();
+
+    if len < packet.len() {
+        return Err(anyhow!("the answer of '{server}' is too short"));
+    }
+    // server mode, and a stratum of 0 is a "kiss of death", the server wants us to go away
+    if packet[0] & 0b111 != 4 || packet[1] == 0 {
+        return Err(anyhow!("'{server}' did not give us the time"));
+    }
+    let server_received_at = ntp_timestamp(&packet[32..40]);
+    let server_sent_at = ntp_timestamp(&packet[40..48]);
+
+    let offset = ((server_received_at - sent_at) + (server_sent_at - received_at)) / 2;
+    debug!("the time of '{server}' is off by {offset}");
+    Ok(offset)
+}
+
+/// Keep asking `server` for the time in the background and store the offset in milliseconds
+///
+/// This does not block, the offset stays as it is until the first answer. If the server can't be
+/// reached, we warn and go on with the local time.
+pub fn spawn(server: String, offset_ms: Arc<AtomicI64>) {
+    std::thread::spawn(move || loop {
+        match query(&server) {
+            Ok(offset) => offset_ms.store(offset.num_milliseconds(), Ordering::Relaxed),
+            Err(e) => {
+                warn!("could not get the time from '{server}', using the local time: {e}");
+                debug!("complete error: {e:#?}");
+            }
+        }
+        std::thread::sleep(INTERVAL);
+    });
+}
+
+/// The host and port of `server`, which is like `host`, `host:port` or `[::1]:port`
+fn address(server: &str) -> (&str, u16) {
+    match server.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => {
+            port.parse().map_or((server, PORT), |port| (host, port))
+        }
+        Some((host, port)) if host.starts_with('[') && host.ends_with(']') => port
+            .parse()
+            .map_or((server, PORT), |port| (&host[1..host.len() - 1], port)),
+        _ => (server, PORT),
+    }
+}
+
+/// The time since the unix epoch, as a duration we can calculate with
+fn unix_now() -> chrono::Duration {
+    chrono::Duration::from_std(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default(),
+    )
+    .unwrap_or_default()
+}
+
+/// Read an NTP timestamp, seconds and a fraction of a second since 1900, as time since the unix
+/// epoch
+fn ntp_timestamp(bytes: &[u8]) -> chrono::Duration {
+    let secs = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    let nanos = (u64::from(fraction) * 1_000_000_000) >> 32;
+    chrono::Duration::seconds(i64::from(secs) - NTP_TO_UNIX_SECS)
+        + chrono::Duration::nanoseconds(nanos as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_server_may_have_a_port() {
+        assert_eq!(address("pool.ntp.org"), ("pool.ntp.org", 123));
+        assert_eq!(address("localhost:1123"), ("localhost", 1123));
+        assert_eq!(address("[::1]:1123"), ("::1", 1123));
+        assert_eq!(address("::1"), ("::1", 123));
+    }
+}
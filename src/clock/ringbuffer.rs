@@ -0,0 +1,122 @@
+/// A fixed size buffer that keeps the last `N` values pushed into it
+///
+/// Pushing a new value overwrites the oldest one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingBuffer<T, const N: usize> {
+    buf: [T; N],
+    idx: usize,
+}
+
+impl<T: Default, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default, const N: usize> RingBuffer<T, N> {
+    /// Create a buffer where every slot has the default value
+    ///
+    /// # Panics
+    ///
+    /// `N` is zero, there must be room for at least one value.
+    #[must_use]
+    pub fn new() -> Self {
+        assert!(N > 0, "a ring buffer needs at least one slot");
+        Self {
+            buf: std::array::from_fn(|_| T::default()),
+            idx: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    /// Add a new value, replacing the oldest one
+    pub fn push(&mut self, value: T) {
+        self.idx = (self.idx + 1) % N;
+        self.buf[self.idx] = value;
+    }
+
+    /// The value that was pushed last
+    #[must_use]
+    #[inline]
+    pub const fn current(&self) -> &T {
+        &self.buf[self.idx]
+    }
+
+    /// The value that was pushed before the [current](Self::current) one
+    #[must_use]
+    #[inline]
+    pub const fn previous(&self) -> &T {
+        &self.buf[(self.idx + N - 1) % N]
+    }
+
+    /// All values, from the oldest to the [current](Self::current) one
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buf[self.idx + 1..]
+            .iter()
+            .chain(&self.buf[..=self.idx])
+    }
+}
+
+impl<T: PartialEq, const N: usize> RingBuffer<T, N> {
+    /// Is the [current](Self::current) value different from the [previous](Self::previous) one?
+    #[must_use]
+    #[inline]
+    pub fn changed(&self) -> bool {
+        self.current() != self.previous()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_with_the_default_values() {
+        let buf: RingBuffer<u8, 3> = RingBuffer::new();
+        assert_eq!((buf.current(), buf.previous()), (&0, &0));
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [0, 0, 0]);
+        assert!(!buf.changed());
+    }
+
+    #[test]
+    fn keeps_the_last_values() {
+        let mut buf: RingBuffer<u8, 3> = RingBuffer::new();
+        buf.push(1);
+        assert_eq!((buf.current(), buf.previous()), (&1, &0));
+        assert!(buf.changed());
+        buf.push(2);
+        buf.push(3);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        // the oldest one is overwritten
+        buf.push(3);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [2, 3, 3]);
+        assert!(!buf.changed());
+    }
+
+    #[test]
+    fn wraps_around_many_times() {
+        let mut buf: RingBuffer<u32, 4> = RingBuffer::new();
+        for value in 1..=10 {
+            buf.push(value);
+            assert_eq!(*buf.current(), value);
+            assert_eq!(*buf.previous(), value - 1);
+        }
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn a_single_slot_is_its_own_previous() {
+        let mut buf: RingBuffer<u8, 1> = RingBuffer::new();
+        buf.push(5);
+        assert_eq!((buf.current(), buf.previous()), (&5, &5));
+        assert!(!buf.changed());
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one slot")]
+    fn needs_at_least_one_slot() {
+        let _: RingBuffer<u8, 0> = RingBuffer::new();
+    }
+}
@@ -0,0 +1,111 @@
+//! The sounds that are played when the clock notifies, one for each kind of notification
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Context;
+use libpt::cli::clap::{self, ValueEnum};
+use libpt::log::{debug, trace};
+use rodio::{Decoder, Sink};
+
+use super::audio;
+use super::NotifyKind;
+
+/// A sound that comes with crock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BundledSound {
+    /// a ringing alarm clock
+    Alarm,
+    /// a short and soft ding
+    Notify,
+}
+
+impl BundledSound {
+    /// The encoded sound
+    ///
+    /// Only about 250 KiB together, so let's just include them in the binary and not worry about
+    /// reading them from the fs and somehow making the files be there.
+    #[must_use]
+    pub const fn raw(self) -> &'static [u8] {
+        match self {
+            Self::Alarm => include_bytes!("../../data/media/alarm.mp3"),
+            Self::Notify => {
+                include_bytes!("../../data/media/278142__ricemaster__effect_notify.wav")
+            }
+        }
+    }
+}
+
+impl NotifyKind {
+    /// The bundled sound for this kind of notification, unless another one is configured
+    #[must_use]
+    pub const fn bundled_sound(self) -> BundledSound {
+        match self {
+            Self::Done | Self::WorkOver => BundledSound::Alarm,
+            Self::BreakOver | Self::Milestone => BundledSound::Notify,
+        }
+    }
+}
+
+/// A [bundled sound](BundledSound) or a sound file, like mp3, wav, flac or ogg
+///
+/// ```
+/// use crock::clock::sound::{BundledSound, Sound};
+///
+/// assert_eq!("notify".parse(), Ok(Sound::Bundled(BundledSound::Notify)));
+/// assert_eq!("bell.ogg".parse(), Ok(Sound::File("bell.ogg".into())));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sound {
+    Bundled(BundledSound),
+    File(PathBuf),
+}
+
+impl FromStr for Sound {
+    type Err = String;
+
+    /// The name of a bundled sound, or else the path of a sound file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("the sound can not be empty".to_string());
+        }
+        Ok(BundledSound::from_str(s, true).map_or_else(|_| Self::File(s.into()), Self::Bundled))
+    }
+}
+
+impl Sound {
+    /// Play the sound on the audio output called `audio_device` until it is over
+    ///
+    /// # Errors
+    ///
+    /// * The audio output could not be opened
+    /// * The sound file could not be opened or decoded
+    pub fn play(&self, volume: f32, audio_device: Option<&str>) -> anyhow::Result<()> {
+        let (_stream, stream_handle) = audio::open(audio_device)?;
+        let sink = Sink::try_new(&stream_handle).context("could not play on the audio output")?;
+        sink.set_volume(volume);
+        match self {
+            Self::File(path) => {
+                trace!("playing sound file {}", path.display());
+                let file = std::fs::File::open(path)
+                    .with_context(|| format!("could not open sound file {}", path.display()))?;
+                sink.append(
+                    Decoder::new(std::io::BufReader::new(file)).with_context(|| {
+                        format!("could not decode sound file {}", path.display())
+                    })?,
+                );
+            }
+            Self::Bundled(sound) => {
+                trace!("playing bundled sound {sound:?}");
+                sink.append(
+                    Decoder::new(std::io::Cursor::new(sound.raw()))
+                        .context("could not decode the bundled sound")?,
+                );
+            }
+        }
+        sink.sleep_until_end();
+
+        debug!("played the sound");
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local};
+use libpt::log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::timebar::TimeBarLength;
+use super::Clock;
+
+/// The running timebar, saved so that it can be [resumed](super::ClockConfig::resume) after
+/// quitting
+///
+/// Only the timebars that count from when they were started are saved, the others follow the
+/// wall clock and are always where they should be anyway.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    pub len: TimeBarLength,
+    pub last_reset: DateTime<Local>,
+    /// when the timer was started, for its label
+    pub started_at: DateTime<Local>,
+    pub paused_at: Option<DateTime<Local>>,
+    pub paused_total_secs: i64,
+    pub snoozed_secs: i64,
+    pub repeat_round: u32,
+    pub pomodoro_on_break: bool,
+    pub pomodoro_round: u32,
+}
+
+impl State {
+    /// Where the state is saved, `~/.cache/crock/state.json` on linux
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("state.json"))
+    }
+
+    /// The state of the timebar of `clock`, if there is one that can be resumed
+    #[must_use]
+    pub fn new(clock: &Clock, started_at: DateTime<Local>) -> Option<Self> {
+        let len = clock.timebar_len()?;
+        if len.is_wall_clock() {
+            return None;
+        }
+        Some(Self {
+            len,
+            last_reset: clock.last_reset?,
+            started_at,
+            paused_at: clock.paused_at.filter(|_| clock.paused),
+            paused_total_secs: clock.paused_total.num_seconds(),
+            snoozed_secs: clock.snoozed.num_seconds(),
+            repeat_round: clock.repeat_round,
+            pomodoro_on_break: clock.pomodoro_on_break,
+            pomodoro_round: clock.pomodoro_round,
+        })
+    }
+
+    /// Write the state to its [path](Self::path)
+    ///
+    /// # Errors
+    ///
+    /// There is no cache directory, or the file could not be written.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("no cache directory to save the state"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        std::fs::write(&path, serde_json::to_string(self)?)
+            .with_context(|| format!("could not write the state to {}", path.display()))?;
+        debug!("saved the state to {}", path.display());
+        Ok(())
+    }
+
+    /// Read the state from its [path](Self::path)
+    ///
+    /// # Errors
+    ///
+    /// There is no saved state, or it is not valid.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path().ok_or_else(|| anyhow!("no cache directory to load the state"))?;
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("there is no timebar to resume in {}", path.display()))?;
+        serde_json::from_str(&raw)
+            .with_context(|| format!("could not parse the state in {}", path.display()))
+    }
+
+    /// Continue the saved timebar with `clock`, instead of the timebar it was started with
+    ///
+    /// Returns when the timer was started.
+    ///
+    /// # Errors
+    ///
+    /// The saved timebar can not be resumed.
+    pub fn restore(self, clock: &mut Clock) -> anyhow::Result<DateTime<Local>> {
+        let secs = |secs: i64| std::time::Duration::from_secs(secs.max(0) as u64);
+        clock.clear_timebar();
+        match self.len {
+            TimeBarLength::Timer => clock.config.timer = true,
+            TimeBarLength::Custom(millis) => {
+                clock.config.custom = Some(std::time::Duration::from_millis(millis.unsigned_abs()));
+            }
+            TimeBarLength::Countup(len) => {
+                clock.config.countdown = Some(secs(len - self.snoozed_secs));
+            }
+            TimeBarLength::Pomodoro { work, break_ } => {
+                clock.config.pomodoro = true;
+                clock.config.pomodoro_work = secs(work);
+                clock.config.pomodoro_break = secs(break_);
+            }
+            len => return Err(anyhow!("can not resume the {len:?} timebar")),
+        }
+        clock.last_reset = Some(self.last_reset);
+        clock.paused = self.paused_at.is_some();
+        clock.paused_at = self.paused_at;
+        clock.paused_total = chrono::Duration::seconds(self.paused_total_secs);
+        clock.snoozed = chrono::Duration::seconds(self.snoozed_secs);
+        clock.repeat_round = self.repeat_round;
+        clock.pomodoro_on_break = self.pomodoro_on_break;
+        clock.pomodoro_round = self.pomodoro_round;
+        // it went off while we were not running, don't notify about it after the fact
+        if matches!(self.len, TimeBarLength::Countup(_))
+            && clock.timebar_remaining(clock.now()) == Some(0)
+        {
+            debug!("the resumed countdown is already over");
+            clock.did_notify = true;
+        }
+        debug!("resumed {:?}", self.len);
+        Ok(self.started_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::tests::{fixed_now, parse, parse_at};
+
+    #[test]
+    fn resume_a_countdown() {
+        let start = fixed_now() - chrono::Duration::minutes(10);
+        let mut c = parse(["some exec", "-u", "25m"]);
+        c.last_reset = Some(start);
+        c.paused_total = chrono::Duration::minutes(1);
+        let state = State::new(&c, start).unwrap();
+        let json = serde_json::to_string(&state).unwrap();
+        let mut resumed = parse_at(["some exec", "-m"], fixed_now());
+        let started_at = serde_json::from_str::<State>(&json)
+            .unwrap()
+            .restore(&mut resumed)
+            .unwrap();
+        assert_eq!(started_at, start);
+        assert_eq!(resumed.timebar_len(), Some(TimeBarLength::Countup(25 * 60)));
+        assert_eq!(
+            resumed.timebar_remaining(start + chrono::Duration::minutes(10)),
+            Some(16 * 60)
+        );
+        assert!(!resumed.did_notify);
+
+        // one that is long over is finished
+        c.last_reset = Some(start - chrono::Duration::hours(1));
+        State::new(&c, start)
+            .unwrap()
+            .restore(&mut resumed)
+            .unwrap();
+        assert!(resumed.is_finished());
+    }
+
+    #[test]
+    fn the_minute_can_not_be_resumed() {
+        assert_eq!(State::new(&parse(["some exec", "-m"]), fixed_now()), None);
+    }
+}
@@ -0,0 +1,80 @@
+//! A short click every second, like a metronome
+
+use std::sync::mpsc::{self, Sender};
+
+use anyhow::Context;
+use libpt::log::{debug, error};
+use rodio::buffer::SamplesBuffer;
+use rodio::{Sink, Source};
+
+use super::audio;
+
+/// The samples per second of the click
+const SAMPLE_RATE: u32 = 44_100;
+/// How long the click is, short enough to not blur into the next one
+const CLICK_MILLIS: u32 = 15;
+/// The pitch of the click
+const CLICK_HZ: f32 = 2000.0;
+
+/// Plays the click on its own thread, which keeps the audio output open
+///
+/// The audio output can not be moved between threads, so we send the ticks to it instead of
+/// opening a new one every second.
+#[derive(Debug, Clone)]
+pub struct Ticker {
+    ticks: Sender<()>,
+}
+
+impl Ticker {
+    /// Open the [audio output](audio::open) in the background and wait for ticks
+    ///
+    /// If the audio output can't be opened, we log it and the ticks go nowhere.
+    #[must_use]
+    pub fn spawn(volume: f32, audio_device: Option<String>) -> Self {
+        let (ticks, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let output = audio::open(audio_device.as_deref()).and_then(|(stream, handle)| {
+                let sink = Sink::try_new(&handle).context("could not play on the audio output")?;
+                Ok((stream, sink))
+            });
+            let (_stream, sink) = match output {
+                Ok(output) => output,
+                Err(e) => {
+                    error!("could not play the tick sound: {e}");
+                    debug!("complete error: {e:#?}");
+                    return;
+                }
+            };
+            sink.set_volume(volume);
+            // decoded once, the clones share the samples
+            let click = click().buffered();
+            while receiver.recv().is_ok() {
+                // don't pile up clicks if we fall behind
+                if sink.empty() {
+                    sink.append(click.clone());
+                }
+            }
+        });
+        Self { ticks }
+    }
+
+    /// Play the click once
+    pub fn tick(&self) {
+        // the thread is only gone if the audio output could not be opened
+        let _ = self.ticks.send(());
+    }
+}
+
+/// A short click, a high sine that fades out quickly
+#[allow(clippy::cast_precision_loss)] // just a few hundred samples
+fn click() -> SamplesBuffer<f32> {
+    let len = SAMPLE_RATE * CLICK_MILLIS / 1000;
+    let samples: Vec<f32> = (0..len)
+        .map(|i| {
+            let time = i as f32 / SAMPLE_RATE as f32;
+            let fade = 1.0 - i as f32 / len as f32;
+            (time * CLICK_HZ * std::f32::consts::TAU).sin() * fade
+        })
+        .collect();
+    SamplesBuffer::new(1, SAMPLE_RATE, samples)
+}
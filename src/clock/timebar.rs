@@ -1,17 +1,112 @@
 use std::fmt::Display;
 
-use chrono::Duration;
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
 
+/// A running timebar, everything needed to say how full it is
+///
+/// The [Clock](super::Clock) keeps track of this while it runs, see
+/// [`Clock::timebar`](super::Clock::timebar).
+///
+/// ```
+/// use chrono::{Duration, Local};
+/// use crock::clock::timebar::{TimeBar, TimeBarLength};
+///
+/// let start = Local::now();
+/// let bar = TimeBar::new(TimeBarLength::Custom(60_000), start);
+/// assert_eq!(bar.ratio(start), 0.0);
+/// assert_eq!(bar.ratio(start + Duration::seconds(30)), 0.5);
+/// assert_eq!(bar.ratio(start + Duration::seconds(60)), 1.0);
+/// // before the start and after the end, the ratio stays in its bounds
+/// assert_eq!(bar.ratio(start - Duration::seconds(5)), 0.0);
+/// assert_eq!(bar.ratio(start + Duration::seconds(90)), 1.0);
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeBar {
+    /// how long the timebar is
+    pub length: TimeBarLength,
+    /// when the timebar was empty
+    pub last_reset: DateTime<Local>,
+    /// how long the timebar was paused since the last reset, this time does not count
+    pub paused: Duration,
+    /// the [Pomodoro](TimeBarLength::Pomodoro) timebar is in its break, which has another length
+    pub on_break: bool,
+    /// count the milliseconds instead of whole seconds, for the
+    /// [smooth timebar](super::ClockConfig::smooth_bar)
+    pub smooth: bool,
+}
+
+impl TimeBar {
+    /// A timebar of `length` that was empty at `last_reset` and was never paused
+    #[must_use]
+    pub const fn new(length: TimeBarLength, last_reset: DateTime<Local>) -> Self {
+        Self {
+            length,
+            last_reset,
+            paused: Duration::zero(),
+            on_break: false,
+            smooth: false,
+        }
+    }
+
+    /// How full the timebar is at `current_time`, from 0.0 to 1.0
+    ///
+    /// The ratio is 0.0 at the [last reset](Self::last_reset) and 1.0 once the length has passed,
+    /// and it is clamped to that range before and after. Only whole seconds count unless it is
+    /// [smooth](Self::smooth), so the timebar fills up in steps of one second. A timebar that is
+    /// not longer than zero, like one counting down to a date that has already passed, is always
+    /// full.
+    ///
+    /// The [Timer](TimeBarLength::Timer) is never full, its ratio says nothing.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // okay, good to know, but I accept the loss. It
+                                          // shouldn't come to more than 2^52 seconds anyway
+    pub fn ratio(&self, current_time: DateTime<Local>) -> f64 {
+        let len = self.length.phase_millis(self.on_break);
+        if len <= 0 {
+            return 1.0;
+        }
+        let since = current_time.signed_duration_since(self.last_reset) - self.paused;
+        let since = if self.smooth {
+            since.num_milliseconds()
+        } else {
+            since.num_seconds() * 1000
+        };
+        (since as f64 / len as f64).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum TimeBarLength {
     Timer,
+    #[default]
     Minute,
     Hour,
+    /// the length is in milliseconds, so that it can be shorter than a second
+    #[serde(rename = "CustomMillis")]
     Custom(i64),
-    /// implementing a bar that would grow smaller would be weird, so it's a count up instead of
-    /// a countdown
+    /// the bar fills up like the others, so it's a count up instead of a countdown. It can
+    /// [drain](super::ClockConfig::drain) instead
     Countup(i64),
     Day,
+    /// starts on monday
+    Week,
+    /// the length is the seconds of the current month, they are not all the same
+    Month(i64),
+    /// the length is the seconds of the current year, leap years have one more day
+    Year(i64),
+    /// counts up to a time of the day, the length is the seconds from the start until then
+    Alarm(i64),
+    /// counts down to a date and time, the length is the seconds from the start until then
+    Until(i64),
+    /// alternates between working and taking a break, both lengths are in seconds
+    Pomodoro {
+        work: i64,
+        break_: i64,
+    },
+    /// counts down the current step of the [intervals](super::ClockConfig::intervals), the length
+    /// is the seconds of that step
+    Interval(i64),
 }
 
 impl TimeBarLength {
@@ -19,29 +114,85 @@ impl TimeBarLength {
         match self {
             Self::Minute => 60,
             Self::Day => 24 * 60 * 60,
+            Self::Week => 7 * 24 * 60 * 60,
             Self::Hour => 60 * 60,
             Self::Timer => 1,
-            Self::Custom(secs) | Self::Countup(secs) => secs,
+            // a started second counts as a whole one
+            Self::Custom(millis) => (millis + 999) / 1000,
+            Self::Countup(secs)
+            | Self::Alarm(secs)
+            | Self::Until(secs)
+            | Self::Interval(secs)
+            | Self::Month(secs)
+            | Self::Year(secs) => secs,
+            Self::Pomodoro { work, .. } => work,
         }
     }
-}
 
-impl From<TimeBarLength> for chrono::Duration {
-    fn from(value: TimeBarLength) -> Self {
-        Self::new(value.as_secs(), 0).expect("seconds out of bounds, cannot create duration")
+    /// Length in milliseconds, only the [custom](Self::Custom) timebar is more exact than
+    /// [`as_secs`](Self::as_secs)
+    pub(crate) const fn as_millis(self) -> i64 {
+        match self {
+            Self::Custom(millis) => millis,
+            _ => self.as_secs() * 1000,
+        }
+    }
+
+    /// Length of the current phase in milliseconds, see [`phase_secs`](Self::phase_secs)
+    pub(crate) const fn phase_millis(self, on_break: bool) -> i64 {
+        match self {
+            Self::Pomodoro { break_, .. } if on_break => break_ * 1000,
+            _ => self.as_millis(),
+        }
+    }
+
+    /// Length of the current phase in seconds
+    ///
+    /// This is only different from [`as_secs`](Self::as_secs) for the
+    /// [Pomodoro](Self::Pomodoro) timebar while taking a break.
+    pub(crate) const fn phase_secs(self, on_break: bool) -> i64 {
+        match self {
+            Self::Pomodoro { break_, .. } if on_break => break_,
+            _ => self.as_secs(),
+        }
+    }
+
+    /// Is this timebar bound to the wall clock instead of just counting from when it was started?
+    pub(crate) const fn is_wall_clock(self) -> bool {
+        matches!(
+            self,
+            Self::Minute
+                | Self::Hour
+                | Self::Day
+                | Self::Week
+                | Self::Month(_)
+                | Self::Year(_)
+                | Self::Alarm(_)
+                | Self::Until(_)
+        )
+    }
+
+    /// Does this timebar follow the calendar, so that its start and end are dates?
+    pub(crate) const fn is_calendar(self) -> bool {
+        matches!(self, Self::Week | Self::Month(_) | Self::Year(_))
     }
 }
 
-impl Default for TimeBarLength {
-    fn default() -> Self {
-        Self::Minute
+impl From<TimeBarLength> for chrono::Duration {
+    fn from(value: TimeBarLength) -> Self {
+        Self::milliseconds(value.as_millis())
     }
 }
 
 impl Display for TimeBarLength {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if *self == Self::Timer {
-            return write!(f, "");
+        match self {
+            Self::Timer => return write!(f, ""),
+            // humantime would show the odd days and hours of the months and years
+            Self::Week => return write!(f, "1week"),
+            Self::Month(_) => return write!(f, "1month"),
+            Self::Year(_) => return write!(f, "1year"),
+            _ => (),
         }
         let buf = match self {
             Self::Minute => humantime::Duration::from(
@@ -59,13 +210,59 @@ impl Display for TimeBarLength {
                     .to_std()
                     .expect("could not convert chrono time to std time"),
             ),
-            Self::Custom(secs) | Self::Countup(secs) => humantime::Duration::from(
+            Self::Custom(millis) => humantime::Duration::from(
+                Duration::milliseconds(*millis)
+                    .to_std()
+                    .expect("could not convert chrono time to std time"),
+            ),
+            Self::Countup(secs)
+            | Self::Alarm(secs)
+            | Self::Until(secs)
+            | Self::Interval(secs)
+            | Self::Pomodoro { work: secs, .. } => humantime::Duration::from(
                 Duration::seconds(*secs)
                     .to_std()
                     .expect("could not convert chrono time to std time"),
             ),
-            Self::Timer => unreachable!(),
+            Self::Timer | Self::Week | Self::Month(_) | Self::Year(_) => unreachable!(),
         };
         write!(f, "{buf}")
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // the ratios are exact
+mod tests {
+    use super::*;
+    use crate::clock::tests::fixed_now;
+
+    #[test]
+    fn the_ratio_counts_the_phase_and_leaves_out_the_pauses() {
+        let start = fixed_now();
+        let mut bar = TimeBar::new(
+            TimeBarLength::Pomodoro {
+                work: 60,
+                break_: 20,
+            },
+            start,
+        );
+        bar.on_break = true;
+        assert_eq!(bar.ratio(start + Duration::seconds(10)), 0.5);
+        bar.paused = Duration::seconds(10);
+        assert_eq!(bar.ratio(start + Duration::seconds(10)), 0.0);
+        assert_eq!(
+            TimeBar::new(TimeBarLength::Until(0), start).ratio(start),
+            1.0
+        );
+    }
+
+    #[test]
+    fn the_smooth_timebar_counts_the_milliseconds() {
+        let start = fixed_now();
+        let half = start + Duration::milliseconds(500);
+        let mut bar = TimeBar::new(TimeBarLength::Custom(2000), start);
+        assert_eq!(bar.ratio(half), 0.0);
+        bar.smooth = true;
+        assert_eq!(bar.ratio(half), 0.25);
+    }
+}
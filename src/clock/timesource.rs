@@ -0,0 +1,84 @@
+//! Where the clock gets the current time from, so that tests can move the time themselves
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+
+/// Something that knows what time it is
+///
+/// The [`Clock`](super::Clock) asks its time source whenever it needs the current time, see
+/// [`Clock::now`](super::Clock::now). The [`SystemTime`] is used unless another one is
+/// [set](super::Clock::set_time_source).
+///
+/// ```
+/// use crock::clock::timesource::{SteppableTime, TimeSource};
+///
+/// let start = chrono::Local::now();
+/// let time = SteppableTime::new(start);
+/// time.advance(chrono::Duration::seconds(90));
+/// assert_eq!(time.now(), start + chrono::Duration::seconds(90));
+/// ```
+pub trait TimeSource: std::fmt::Debug + Send + Sync {
+    /// The current time
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The time of the system clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SystemTime;
+
+impl TimeSource for SystemTime {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// Always the same time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedTime(pub DateTime<Local>);
+
+impl TimeSource for FixedTime {
+    fn now(&self) -> DateTime<Local> {
+        self.0
+    }
+}
+
+/// A time that stands still until it is moved on by hand
+#[derive(Debug)]
+pub struct SteppableTime {
+    now: Mutex<DateTime<Local>>,
+}
+
+impl SteppableTime {
+    /// Start at `now`
+    #[must_use]
+    pub const fn new(now: DateTime<Local>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Move the time on by `step`, or back if it is negative
+    ///
+    /// # Panics
+    ///
+    /// Another thread panicked while moving the time.
+    pub fn advance(&self, step: chrono::Duration) {
+        *self.now.lock().expect("the time source was poisoned") += step;
+    }
+
+    /// Jump to `now`
+    ///
+    /// # Panics
+    ///
+    /// Another thread panicked while moving the time.
+    pub fn set(&self, now: DateTime<Local>) {
+        *self.now.lock().expect("the time source was poisoned") = now;
+    }
+}
+
+impl TimeSource for SteppableTime {
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().expect("the time source was poisoned")
+    }
+}
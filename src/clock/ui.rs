@@ -1,44 +1,68 @@
-use chrono::{DateTime, Local, SubsecRound, Timelike};
-use libpt::log::{debug, error, trace};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, SubsecRound, Timelike, Utc};
+use chrono_tz::Tz;
+use libpt::cli::clap::{self, ValueEnum};
+use libpt::log::{debug, trace};
+use ratatui::buffer::Buffer;
 use ratatui::layout::{Alignment, Rect};
-use ratatui::style::{Style, Stylize};
-use ratatui::widgets::{Block, LineGauge, Padding, Paragraph};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, LineGauge, Padding, Paragraph, Sparkline, Widget, Wrap};
+use tui_big_text::PixelSize;
 
+use crate::clock::astro::MoonPhase;
+use crate::clock::chess::{self, ChessState};
+use crate::clock::ringbuffer::RingBuffer;
 use crate::clock::timebar::TimeBarLength;
 
-use super::Clock;
+use super::{format_duration, Clock};
 
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
 pub const TIME_FORMAT: &str = "%H:%M:%S";
 pub const TIME_FORMAT_SHORT: &str = "%H:%M";
+pub const TIME_FORMAT_12H: &str = "%I:%M:%S %p";
+pub const TIME_FORMAT_SHORT_12H: &str = "%I:%M %p";
+/// How many seconds of the timebar are kept for the [sparkline](super::ClockConfig::sparkline),
+/// more than a terminal is wide
+pub const HISTORY_LEN: usize = 512;
+
+/// What is shown at one point in time
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Sample {
+    now: DateTime<Local>,
+    fdate: String,
+    ftime: String,
+    timebar_ratio: Option<f64>,
+}
 
-// TODO: make this a ringbuffer with a custom struct inside?
 #[derive(Debug, Clone, PartialEq)]
 pub struct Data {
-    now: [DateTime<Local>; 2],
-    fdate: [String; 2],
-    ftime: [String; 2],
-    timebar_ratio: [Option<f64>; 2],
+    samples: RingBuffer<Sample, 2>,
+    /// how full the timebar was in percent, once for each second
+    history: RingBuffer<u64, HISTORY_LEN>,
 
     timebar_type: Option<TimeBarLength>,
     started_at: DateTime<Local>,
-
-    idx: usize,
+    /// how full each of the [stacked timebars](Clock::stacked_bars) is
+    stacked_ratios: Vec<f64>,
+    /// a change of the timebar ratio alone is a change too
+    smooth_bar: bool,
 }
 
 impl Data {
+    #[must_use]
     pub fn new(timebar_type: Option<TimeBarLength>) -> Self {
-        let mut this = Self {
-            now: [DateTime::default(); 2],
-            fdate: [String::new(), String::new()],
-            ftime: [String::new(), String::new()],
-            timebar_ratio: [Option::default(); 2],
-            started_at: Local::now(),
-            idx: usize::default(),
-
+        Self {
+            samples: RingBuffer::new(),
+            history: RingBuffer::new(),
+            started_at: Local::now().trunc_subsecs(0),
             timebar_type,
-        };
-        this.started_at = this.started_at.round_subsecs(0);
-        this
+            stacked_ratios: Vec::new(),
+            smooth_bar: false,
+        }
     }
     pub fn update(
         &mut self,
@@ -47,175 +71,1973 @@ impl Data {
         ftime: String,
         timebar_ratio: Option<f64>,
     ) {
-        self.idx ^= 1;
-        self.now[self.idx] = now;
-        self.fdate[self.idx] = fdate;
-        self.ftime[self.idx] = ftime;
-        self.timebar_ratio[self.idx] = timebar_ratio;
-        #[cfg(debug_assertions)]
-        if self.changed() {
+        self.samples.push(Sample {
+            now,
+            fdate,
+            ftime,
+            timebar_ratio,
+        });
+        if self.time_changed() {
+            if let Some(ratio) = self.timebar_ratio() {
+                #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                self.history
+                    .push((ratio.clamp(0.0, 1.0) * 100.0).round() as u64);
+            }
+            #[cfg(debug_assertions)]
             trace!("update with change: {:#?}", self);
         }
     }
 
+    /// start counting the [Timer](TimeBarLength::Timer) from `now` on
+    pub fn reset_started_at(&mut self, now: DateTime<Local>) {
+        self.started_at = now.trunc_subsecs(0);
+    }
+
+    /// continue the [Timer](TimeBarLength::Timer) that was started at `started_at`
+    pub const fn set_started_at(&mut self, started_at: DateTime<Local>) {
+        self.started_at = started_at;
+    }
+
+    /// how full each of the [stacked timebars](Clock::stacked_bars) is now
+    pub fn set_stacked_ratios(&mut self, ratios: Vec<f64>) {
+        self.stacked_ratios = ratios;
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn stacked_ratios(&self) -> &[f64] {
+        &self.stacked_ratios
+    }
+
+    /// how full the timebar was in percent over the last `len` seconds, the oldest first
+    #[must_use]
+    pub fn history(&self, len: usize) -> Vec<u64> {
+        let history: Vec<u64> = self.history.iter().copied().collect();
+        history[history.len().saturating_sub(len)..].to_vec()
+    }
+
+    /// when the [Timer](TimeBarLength::Timer) was started
+    #[must_use]
+    pub const fn started_at(&self) -> &DateTime<Local> {
+        &self.started_at
+    }
+
     /// did the data change with the last update?
     #[must_use]
     #[inline]
     pub fn changed(&self) -> bool {
         //  the timebar ratio is discarded, so that we only render the ui when the time
-        //  (second) changes
-        self.fdate[0] != self.fdate[1] || self.ftime[0] != self.ftime[1]
+        //  (second) changes, unless the timebar is smooth
+        let (current, previous) = (self.samples.current(), self.samples.previous());
+        self.time_changed() || (self.smooth_bar && current.timebar_ratio != previous.timebar_ratio)
+    }
+
+    /// did the shown date or time change with the last update?
+    fn time_changed(&self) -> bool {
+        let (current, previous) = (self.samples.current(), self.samples.previous());
+        current.fdate != previous.fdate || current.ftime != previous.ftime
+    }
+
+    /// Also count a change of the timebar ratio as a [change](Self::changed), for the
+    /// [smooth timebar](super::ClockConfig::smooth_bar)
+    pub const fn set_smooth_bar(&mut self, smooth_bar: bool) {
+        self.smooth_bar = smooth_bar;
     }
 
     #[must_use]
     #[inline]
     pub fn fdate(&self) -> &str {
-        &self.fdate[self.idx]
+        &self.samples.current().fdate
     }
 
     #[must_use]
     #[inline]
     pub fn ftime(&self) -> &str {
-        &self.ftime[self.idx]
+        &self.samples.current().ftime
     }
 
     #[must_use]
     #[inline]
-    #[allow(clippy::missing_const_for_fn)] // why should it be okay to make this const? This is
-                                           // a custom ringbuffer!
-    pub fn now(&self) -> &DateTime<Local> {
-        &self.now[self.idx]
+    pub const fn now(&self) -> &DateTime<Local> {
+        &self.samples.current().now
     }
 
     #[must_use]
     #[inline]
-    #[allow(clippy::missing_const_for_fn)] // no it's not const
     pub fn timebar_ratio(&self) -> Option<f64> {
-        if self.timebar_type.is_some() && self.timebar_type.unwrap() == TimeBarLength::Timer {
+        if self.timebar_type == Some(TimeBarLength::Timer) {
             return Some(0.0);
         }
-        self.timebar_ratio[self.idx]
+        self.samples.current().timebar_ratio
     }
 }
 
-pub fn timebarw<'a>(
-    clock: &mut Clock,
+/// The size of the big clock, see [`PixelSize`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Font {
+    Full,
+    Half,
+    Quadrant,
+    Sextant,
+}
+
+impl Font {
+    #[must_use]
+    pub const fn pixel_size(self) -> PixelSize {
+        match self {
+            Self::Full => PixelSize::Full,
+            Self::Half => PixelSize::HalfHeight,
+            Self::Quadrant => PixelSize::Quadrant,
+            Self::Sextant => PixelSize::Sextant,
+        }
+    }
+}
+
+/// How the timebar is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum BarStyle {
+    /// A thin line
+    #[default]
+    Line,
+    /// A solid block, with the percentage in it if `--percent` is given
+    Block,
+}
+
+/// The colors of the clock, some of them are easier to tell apart for more people
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Palette {
+    /// A red clock and a blue timebar
+    #[default]
+    Default,
+    /// Bright colors on black
+    HighContrast,
+    /// Blue and orange instead of green and red, for red-green color blindness
+    Deuteranopia,
+    /// Like deuteranopia, but without the dark reds that are hard to see with protanopia
+    Protanopia,
+    /// No colors at all, the end of the timebar is shown with text and blinking
+    Mono,
+}
+
+impl Palette {
+    /// Okabe-Ito colors, which stay apart for the common kinds of color blindness
+    const ORANGE: Color = Color::Rgb(230, 159, 0);
+    const SKY_BLUE: Color = Color::Rgb(86, 180, 233);
+    const BLUE: Color = Color::Rgb(0, 114, 178);
+    const VERMILLION: Color = Color::Rgb(213, 94, 0);
+    const YELLOW: Color = Color::Rgb(240, 228, 66);
+
+    /// The color of the clock digits
+    #[must_use]
+    pub const fn digits(self) -> Color {
+        match self {
+            Self::Default => Color::Red,
+            Self::HighContrast => Color::White,
+            Self::Deuteranopia | Self::Protanopia => Self::ORANGE,
+            Self::Mono => Color::Reset,
+        }
+    }
+
+    /// The color of the timebar
+    #[must_use]
+    pub const fn bar(self) -> Color {
+        match self {
+            Self::Default => Color::Blue,
+            Self::HighContrast => Color::LightYellow,
+            Self::Deuteranopia => Self::BLUE,
+            Self::Protanopia => Self::SKY_BLUE,
+            Self::Mono => Color::Reset,
+        }
+    }
+
+    /// The color of the date and other small text
+    #[must_use]
+    pub const fn date(self) -> Color {
+        match self {
+            Self::Default => Color::Blue,
+            Self::HighContrast => Color::White,
+            Self::Deuteranopia | Self::Protanopia => Self::SKY_BLUE,
+            Self::Mono => Color::Reset,
+        }
+    }
+
+    /// The color of anything that needs attention, like the timebar that is nearly done
+    #[must_use]
+    pub const fn warning(self) -> Color {
+        match self {
+            Self::Default => Color::Red,
+            Self::HighContrast => Color::LightRed,
+            Self::Deuteranopia => Self::VERMILLION,
+            Self::Protanopia => Self::YELLOW,
+            Self::Mono => Color::Reset,
+        }
+    }
+
+    /// The color of the timebar once it is done
+    #[must_use]
+    pub const fn done(self) -> Color {
+        match self {
+            Self::Default => Color::Yellow,
+            Self::HighContrast => Color::LightGreen,
+            Self::Deuteranopia | Self::Protanopia => Self::BLUE,
+            Self::Mono => Color::Reset,
+        }
+    }
+
+    /// The background behind everything, if the palette needs one
+    #[must_use]
+    pub const fn background(self) -> Option<Color> {
+        match self {
+            Self::HighContrast => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    /// The [gradient](super::ClockConfig::gradient) color of the timebar at `ratio`
+    ///
+    /// Green to red is the hardest to tell apart with color blindness, so those palettes go from
+    /// blue to orange.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // between two u8
+    pub fn gradient(self, ratio: f64) -> Color {
+        let blend = |from: u8, to: u8| {
+            (f64::from(to) - f64::from(from)).mul_add(ratio.clamp(0.0, 1.0), f64::from(from)) as u8
+        };
+        match self {
+            Self::Default | Self::HighContrast => gradient_color(ratio),
+            // from the blue to the orange above
+            Self::Deuteranopia | Self::Protanopia => {
+                Color::Rgb(blend(0, 230), blend(114, 159), blend(178, 0))
+            }
+            Self::Mono => Color::Reset,
+        }
+    }
+}
+
+/// The timebar in one of the [`BarStyle`]s
+#[derive(Debug, Clone)]
+pub enum TimeBarWidget<'a> {
+    Line(LineGauge<'a>),
+    Block(Gauge<'a>),
+}
+
+impl Widget for TimeBarWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        match self {
+            Self::Line(gauge) => gauge.render(area, buf),
+            Self::Block(gauge) => gauge.render(area, buf),
+        }
+    }
+}
+
+/// How many lines the big clock needs with `pixel_size`, with an empty line below it
+#[must_use]
+pub const fn clock_height(pixel_size: PixelSize) -> u16 {
+    match pixel_size {
+        PixelSize::Full => 8,
+        PixelSize::ThirdHeight | PixelSize::Sextant => 4,
+        _ => 5,
+    }
+}
+
+/// Can the big clock be rendered at all, with [`PixelSize::Quadrant`] and the timebar above it?
+///
+/// A quadrant glyph is 4 cells wide.
+#[must_use]
+pub fn big_clock_fits(inner_rect: Rect, ftime: &str) -> bool {
+    usize::from(inner_rect.width) >= ftime.chars().count() * 4
+        && inner_rect.height >= 3 + clock_height(PixelSize::Quadrant)
+}
+
+/// Can the big clock be rendered with [`PixelSize::Full`](tui_big_text::PixelSize::Full)?
+///
+/// A full size glyph is 8 cells wide, we want some space around them.
+#[must_use]
+pub fn big_clock_fits_full(inner_rect: Rect, ftime: &str) -> bool {
+    usize::from(inner_rect.width) > ftime.chars().count() * 10
+}
+
+/// Is there enough space to render each timezone of the world clock with big text?
+///
+/// A quadrant glyph is 4 cells wide and 4 cells high, we also want an empty line between them.
+#[must_use]
+pub fn world_fits_big(inner_rect: Rect, zones: usize, ftime: &str) -> bool {
+    usize::from(inner_rect.height) >= zones * 5
+        && usize::from(inner_rect.width) * 68 / 100 >= ftime.chars().count() * 4
+}
+
+/// The whole clock as the TUI shows it, with the border, date, timebar and big clock
+///
+/// This does not own the terminal or the event loop, so it can also be rendered as a part of
+/// another ratatui app. Keep the [`Data`] updated and call
+/// [`maybe_reset_since_zero`](Clock::maybe_reset_since_zero) and
+/// [`notify_when_done`](Clock::notify_when_done) regularly, like [`Clock::run`] does.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockWidget<'a> {
+    clock: &'a Clock,
+    data: &'a Data,
+}
+
+impl<'a> ClockWidget<'a> {
+    #[must_use]
+    pub const fn new(clock: &'a Clock, data: &'a Data) -> Self {
+        Self { clock, data }
+    }
+
+    /// Where the widgets of the clock are when it is rendered in `area`, see
+    /// [`Clock::partition`]
+    #[must_use]
+    pub fn areas(&self, area: Rect) -> HashMap<&'static str, Rect> {
+        let inner_rect = self.space(area).inner(area);
+        self.clock
+            .partition(inner_rect, self.pixel_size(inner_rect))
+    }
+
+    /// The size of the big clock, the [font](Clock::font) or whatever fits into `inner_rect`
+    ///
+    /// The binary clock takes as much space as the quadrant font.
+    fn pixel_size(&self, inner_rect: Rect) -> PixelSize {
+        match self.clock.config.font {
+            Some(font) => font.pixel_size(),
+            None if !self.clock.config.binary
+                && big_clock_fits_full(inner_rect, self.data.ftime()) =>
+            {
+                PixelSize::Full
+            }
+            None => PixelSize::Quadrant,
+        }
+    }
+
+    /// The border around everything, or just the padding without a border
+    fn space(&self, area: Rect) -> Block<'a> {
+        // at most half of the space goes to the padding, so the clock always gets some
+        let (horizontal, vertical) = self
+            .clock
+            .config
+            .padding
+            .map_or((area.width / 16, area.height / 16), |padding| {
+                (padding.min(area.width / 4), padding.min(area.height / 4))
+            });
+        let padding = Padding::new(horizontal, horizontal, vertical, vertical);
+        if self.clock.config.no_border {
+            return Block::default().padding(padding);
+        }
+        let mut space = Block::bordered()
+            .padding(padding)
+            .title(
+                self.clock
+                    .config
+                    .title
+                    .as_deref()
+                    .unwrap_or(env!("CARGO_PKG_NAME")),
+            )
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().bold());
+        if !self.clock.config.no_version {
+            space = space.title_bottom(env!("CARGO_PKG_VERSION"));
+        }
+        if self.clock.config.ascii {
+            space = space.border_set(ASCII_BORDER);
+        }
+        space
+    }
+}
+
+impl Widget for ClockWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let space = self.space(area);
+        let inner_rect = space.inner(area);
+        if let Some(background) = self.clock.config.palette.background() {
+            buf.set_style(area, Style::new().bg(background).fg(Color::White));
+        }
+        space.render(area, buf);
+        let Self { clock, data } = self;
+        if !clock.config.world.is_empty() {
+            render_world(clock, data, inner_rect, buf);
+        } else if let Some(chess) = &clock.chess {
+            render_chess(clock, chess, data, inner_rect, buf);
+        } else if clock.config.font.is_none()
+            && !clock.config.compact
+            && !clock.config.bar_only
+            && !big_clock_fits(inner_rect, data.ftime())
+        {
+            // a chosen font is shown even if it does not fit, the compact clock and the bar
+            // always fit
+            small_clockw(clock, data).render(inner_rect, buf);
+        } else {
+            self.render_clock(area, inner_rect, buf);
+        }
+        // some widgets have their own colors, the palette does not cover them all
+        if clock.config.no_color {
+            buf.set_style(area, Style::new().fg(Color::Reset).bg(Color::Reset));
+        }
+        if clock.flash_inverted() {
+            buf.set_style(area, Style::new().reversed());
+        }
+    }
+}
+
+impl ClockWidget<'_> {
+    /// Render the big clock with everything around it
+    #[allow(clippy::cast_possible_truncation)] // if we have that much padding, please truncate
+    fn render_clock(self, area: Rect, inner_rect: Rect, buf: &mut Buffer) {
+        let parts = self.areas(area);
+        let mut clockw = tui_big_text::BigText::builder();
+        clockw.pixel_size(self.pixel_size(inner_rect));
+        let Self { clock, data } = self;
+
+        let ftime = if clock.config.no_leading_zero {
+            strip_leading_zero(data.ftime())
+        } else {
+            data.ftime()
+        };
+        // every glyph has the same width, so the digits stay where they are without the colons
+        let ftime = if clock.config.blink && data.now().second() % 2 == 1 {
+            ftime.replace(':', " ")
+        } else {
+            ftime.to_owned()
+        };
+        let clockw = clockw
+            .style(Style::new().fg(clock.digit_color()))
+            .lines(vec![ftime.clone().into()])
+            .alignment(Alignment::Center)
+            .build()
+            .expect("could not render time widget");
+
+        // render the timebar which counts up to the full minute and so on
+        //
+        // Will not be rendered if it is None
+        // without a date on the left, there is nothing to balance on the right
+        let timebarw_padding = if clock.config.bar_only {
+            [0, 0]
+        } else {
+            [
+                (f32::from(parts["timebarw"].width) * 0.43) as u16,
+                (f32::from(parts["timebarw"].width) * 0.25) as u16,
+            ]
+        };
+        let padding = if inner_rect.width > 80 {
+            timebarw_padding[0]
+        } else {
+            timebarw_padding[1]
+        };
+        if clock.config.ascii {
+            if clock.timebar_len().is_some() {
+                let ratio = data.timebar_ratio().unwrap_or_default();
+                let width = parts["timebarw"].width.saturating_sub(padding);
+                ascii_barw(
+                    filled_ratio(clock, ratio),
+                    width,
+                    timebar_style(clock, data, ratio),
+                )
+                .render(parts["timebarw"], buf);
+            }
+        } else if let Some(timebarw) = timebarw(clock, data, &timebarw_padding, inner_rect) {
+            timebarw.render(parts["timebarw"], buf);
+        }
+        if let Some(timebarw_label) = timebarw_label(clock, data, &timebarw_padding, inner_rect) {
+            timebarw_label.render(parts["timebarw_label"], buf);
+        }
+        if clock.config.sparkline && clock.timebar_len().is_some() {
+            let history = data.history(usize::from(
+                parts["sparklinew"].width.saturating_sub(padding),
+            ));
+            sparklinew(clock, &history, padding).render(parts["sparklinew"], buf);
+        }
+        render_stacked(clock, data, parts["timebars"], padding, buf);
+
+        // render the small date, the compact clock already has it
+        // long custom date formats might not fit in a single line
+        let mut date = vec![Line::from(data.fdate().to_owned())];
+        if let Some(eta) = eta_text(clock, data).filter(|_| clock.config.show_eta) {
+            date.push(Line::from(eta));
+        }
+        if !clock.config.compact && !clock.config.bar_only {
+            Paragraph::new(date)
+                .fg(clock.config.palette.date())
+                .block(Block::default().padding(Padding::right(2)))
+                .alignment(Alignment::Right)
+                .wrap(Wrap { trim: true })
+                .render(parts["datew"], buf);
+        }
+        // render the clock
+        if clock.config.bar_only {
+            // only the timebar
+        } else if clock.config.compact {
+            compact_clockw(clock, data, &ftime).render(parts["clockw"], buf);
+        } else if clock.config.binary {
+            let time = clock.in_timezone(data.now().trunc_subsecs(0)).time();
+            binaryw(time, clock.digit_color()).render(parts["clockw"], buf);
+        } else if clock.config.ascii {
+            asciiw(&ftime, clock.digit_color()).render(parts["clockw"], buf);
+        } else {
+            clockw.render(parts["clockw"], buf);
+        }
+        if clock.config.with_utc {
+            utcw(clock, data).render(parts["utcw"], buf);
+        }
+        // render the laps of the timer below the clock
+        lapsw(clock, parts["lapsw"].height).render(parts["lapsw"], buf);
+        if clock.shows_zone() {
+            tzw(clock, data).render(parts["tzw"], buf);
+        }
+        if clock.config.moon {
+            moonw(clock, data).render(parts["moonw"], buf);
+        }
+    }
+}
+
+/// The text of `buf`, one line per row without the trailing spaces
+#[must_use]
+pub fn buffer_to_string(buf: &Buffer) -> String {
+    let width = usize::from(buf.area.width.max(1));
+    buf.content
+        .chunks(width)
+        .map(|row| {
+            let line: String = row.iter().map(ratatui::buffer::Cell::symbol).collect();
+            line.trim_end().to_owned() + "\n"
+        })
+        .collect()
+}
+
+/// `ftime` without the leading zero of the hour, "09:05:03" becomes "9:05:03"
+///
+/// Only the first digit is removed, so midnight is still "0:00:00".
+#[must_use]
+pub fn strip_leading_zero(ftime: &str) -> &str {
+    match ftime.strip_prefix('0') {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest,
+        _ => ftime,
+    }
+}
+
+/// The time and date in normal text, for terminals that are too small for the big clock
+#[must_use]
+pub fn small_clockw<'a>(clock: &Clock, data: &Data) -> Paragraph<'a> {
+    Paragraph::new(vec![
+        Line::styled(
+            data.ftime().to_owned(),
+            Style::new().fg(clock.digit_color()).bold(),
+        ),
+        Line::styled(
+            data.fdate().to_owned(),
+            Style::new().fg(clock.config.palette.date()),
+        ),
+        Line::styled("terminal too small", Style::new().dark_gray()),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+}
+
+/// The weekday, date and time in one line, like "Mon 2024-06-10 14:30:05", for the
+/// [compact](super::ClockConfig::compact) clock
+///
+/// `ftime` is the time as the big clock would show it, with the blinking colons and all.
+#[must_use]
+pub fn compact_clockw<'a>(clock: &Clock, data: &Data, ftime: &str) -> Paragraph<'a> {
+    let weekday = clock.localized(&clock.in_timezone(*data.now()), "%a");
+    Paragraph::new(Line::from(vec![
+        Span::styled(
+            format!("{weekday} {} ", data.fdate()),
+            Style::new().fg(clock.config.palette.date()),
+        ),
+        Span::styled(
+            ftime.to_owned(),
+            Style::new().fg(clock.digit_color()).bold(),
+        ),
+    ]))
+    .alignment(Alignment::Center)
+}
+
+/// The time in UTC, for showing it small below the clock
+#[must_use]
+pub fn utcw<'a>(clock: &Clock, data: &Data) -> Paragraph<'a> {
+    Paragraph::new(format!(
+        "UTC {}",
+        clock.localized(&data.now().with_timezone(&Utc), clock.time_format())
+    ))
+    .alignment(Alignment::Center)
+}
+
+/// The timezone of the clock and its offset from UTC, for showing it small in a corner
+#[must_use]
+pub fn tzw<'a>(clock: &Clock, data: &Data) -> Paragraph<'a> {
+    let label = clock.config.timezone.map_or_else(
+        || format!("local UTC{}", data.now().format("%:z")),
+        |tz| zone_label(data.now().with_timezone(&tz)),
+    );
+    Paragraph::new(label)
+        .fg(Color::DarkGray)
+        .block(Block::default().padding(Padding::left(2)))
+}
+
+/// The abbreviation of the timezone at `time` and its offset from UTC, like "JST UTC+09:00"
+///
+/// Zones without an abbreviation of their own only get the offset, like UTC itself.
+#[must_use]
+pub fn zone_label(time: DateTime<Tz>) -> String {
+    let abbreviation = time.format("%Z").to_string();
+    let offset = time.format("%:z");
+    if abbreviation.starts_with(['+', '-']) || abbreviation == "UTC" {
+        format!("UTC{offset}")
+    } else {
+        format!("{abbreviation} UTC{offset}")
+    }
+}
+
+/// The phase of the moon, for showing it small in a corner
+#[must_use]
+pub fn moonw<'a>(clock: &Clock, data: &Data) -> Paragraph<'a> {
+    let phase = MoonPhase::at(data.now());
+    let text = if clock.config.ascii {
+        phase.name().to_string()
+    } else {
+        format!("{} {}", phase.symbol(), phase.name())
+    };
+    Paragraph::new(text)
+        .alignment(Alignment::Right)
+        .block(Block::default().padding(Padding::right(2)))
+}
+
+/// The last laps of the timer that fit into `height` lines, the newest first
+#[must_use]
+pub fn lapsw<'a>(clock: &Clock, height: u16) -> Paragraph<'a> {
+    let lines: Vec<Line> = clock
+        .laps()
+        .iter()
+        .enumerate()
+        .rev()
+        .take(usize::from(height))
+        .map(|(i, lap)| Line::from(format!("lap {}: {}", i + 1, format_duration(*lap))))
+        .collect();
+    Paragraph::new(lines).alignment(Alignment::Center)
+}
+
+/// Render the [stacked timebars](Clock::stacked_bars) into `area`, each with its label below
+fn render_stacked(clock: &Clock, data: &Data, area: Rect, padding: u16, buf: &mut Buffer) {
+    let stacked = clock.stacked_bars();
+    for ((len, ratio), (gauge_area, label_area)) in stacked
+        .iter()
+        .zip(data.stacked_ratios())
+        .zip(Clock::partition_stacked(area, stacked.len()))
+    {
+        let (gauge, label) = stacked_barw(clock, *len, *ratio, padding);
+        if clock.config.ascii {
+            let width = gauge_area.width.saturating_sub(padding);
+            ascii_barw(*ratio, width, Style::default().fg(bar_color(clock, *ratio)))
+                .render(gauge_area, buf);
+        } else {
+            gauge.render(gauge_area, buf);
+        }
+        label.render(label_area, buf);
+    }
+}
+
+/// Render one row with the date and time for each timezone of the world clock
+fn render_world(clock: &Clock, data: &Data, inner_rect: Rect, buf: &mut Buffer) {
+    let now = data.now().trunc_subsecs(0);
+    let big = !clock.config.ascii
+        && world_fits_big(
+            inner_rect,
+            clock.config.world.len(),
+            &clock.localized(&now, clock.time_format()),
+        );
+    let rows = Clock::partition_world(inner_rect, clock.config.world.len(), big);
+    for (tz, row) in clock.config.world.iter().zip(rows) {
+        let time = now.with_timezone(tz);
+        Paragraph::new(vec![
+            Line::from(vec![
+                Span::raw(tz.name()).bold(),
+                Span::raw(format!(" {}", zone_label(time))).dark_gray(),
+            ]),
+            Line::from(clock.localized(&time, clock.date_format())).fg(clock.config.palette.date()),
+        ])
+        .alignment(Alignment::Right)
+        .block(Block::default().padding(Padding::right(2)))
+        .render(row["labelw"], buf);
+        let ftime = clock.localized(&time, clock.time_format());
+        if big {
+            tui_big_text::BigText::builder()
+                .pixel_size(tui_big_text::PixelSize::Quadrant)
+                .style(Style::new().fg(clock.digit_color()))
+                .lines(vec![ftime.into()])
+                .build()
+                .expect("could not render time widget")
+                .render(row["timew"], buf);
+        } else {
+            Paragraph::new(ftime)
+                .fg(clock.digit_color())
+                .bold()
+                .render(row["timew"], buf);
+        }
+    }
+}
+
+/// Render the time of both players of the chess clock side by side
+///
+/// The player whose turn it is is highlighted, the one who ran out of time is red.
+fn render_chess(
+    clock: &Clock,
+    chess: &ChessState,
     data: &Data,
-    timebarw_padding: &[u16],
     inner_rect: Rect,
-) -> Option<LineGauge<'a>> {
-    if clock.timebar_len().is_some() {
-        debug!("time bar ration: {:?}", data.timebar_ratio());
-        let ratio = data.timebar_ratio().unwrap();
-
-        if !clock.did_notify && (ratio - 1.0).abs() < 0.000_001 {
-            if let Some(TimeBarLength::Countup(_)) = clock.timebar_len() {
-                let _ = clock.notify().inspect_err(|e| {
-                    error!("could not notify: {e}");
-                    debug!("complete error: {e:#?}");
-                });
-                clock.did_notify = true;
-            }
+    buf: &mut Buffer,
+) {
+    let times: Vec<String> = (0..2)
+        .map(|player| chess::format_remaining(chess.remaining(player, *data.now())))
+        .collect();
+    let half = Rect {
+        width: inner_rect.width / 2,
+        ..inner_rect
+    };
+    let big = !clock.config.ascii && times.iter().all(|ftime| big_clock_fits(half, ftime));
+    let columns = Clock::partition_chess(inner_rect, big);
+    for (player, (ftime, column)) in times.into_iter().zip(columns).enumerate() {
+        let (label, color) = if chess.loser() == Some(player) {
+            (
+                format!("Player {} - out of time", player + 1),
+                clock.config.palette.warning(),
+            )
+        } else if chess.active() == player && chess.is_running() {
+            (format!("> Player {} <", player + 1), clock.digit_color())
+        } else {
+            (format!("Player {}", player + 1), Color::DarkGray)
+        };
+        Paragraph::new(label)
+            .fg(color)
+            .bold()
+            .alignment(Alignment::Center)
+            .render(column["labelw"], buf);
+        if big {
+            tui_big_text::BigText::builder()
+                .pixel_size(tui_big_text::PixelSize::Quadrant)
+                .style(Style::new().fg(color))
+                .lines(vec![ftime.into()])
+                .alignment(Alignment::Center)
+                .build()
+                .expect("could not render time widget")
+                .render(column["timew"], buf);
+        } else {
+            Paragraph::new(ftime)
+                .fg(color)
+                .bold()
+                .alignment(Alignment::Center)
+                .render(column["timew"], buf);
         }
+    }
+}
 
-        #[allow(clippy::cast_sign_loss)]
-        #[allow(clippy::cast_possible_truncation)]
-        let timebarw = LineGauge::default()
-            .filled_style(if clock.did_notify {
-                Style::default()
-                    .slow_blink()
-                    .bold()
-                    .underlined()
-                    .yellow()
-                    .crossed_out()
-            } else {
-                Style::default().blue()
-            })
-            .unfilled_style(Style::default())
-            .block(
-                Block::default().padding(Padding::right(if inner_rect.width > 80 {
-                    timebarw_padding[0]
+/// How many bits each digit of `HH:MM:SS` can need at most
+const BINARY_DIGIT_BITS: [u32; 6] = [2, 4, 3, 4, 3, 4];
+
+/// The bits of each digit of `HH:MM:SS` in binary coded decimal
+///
+/// There is one column for each digit, the first row of a column is the highest bit (8), the
+/// last row is the lowest bit (1).
+#[must_use]
+pub fn binary_bits(time: NaiveTime) -> [[bool; 4]; 6] {
+    let digits = [
+        time.hour() / 10,
+        time.hour() % 10,
+        time.minute() / 10,
+        time.minute() % 10,
+        time.second() / 10,
+        time.second() % 10,
+    ];
+    digits.map(|digit| [8, 4, 2, 1].map(|bit| digit & bit != 0))
+}
+
+/// Render the time as a binary clock, lit dots are set bits
+#[must_use]
+pub fn binaryw<'a>(time: NaiveTime, color: Color) -> Paragraph<'a> {
+    let bits = binary_bits(time);
+    let lines: Vec<Line> = (0..4)
+        .map(|row| {
+            let mut spans = Vec::new();
+            for (col, digit) in bits.iter().enumerate() {
+                if col > 0 {
+                    spans.push(Span::raw(if col % 2 == 0 { "   " } else { " " }));
+                }
+                spans.push(if 4 - row > BINARY_DIGIT_BITS[col] as usize {
+                    // this digit never needs this bit
+                    Span::raw(" ")
+                } else if digit[row] {
+                    Span::styled("●", Style::new().fg(color))
                 } else {
-                    timebarw_padding[1]
-                })),
-            )
-            .ratio(ratio);
-        Some(timebarw)
+                    Span::raw("○").dark_gray()
+                });
+            }
+            Line::from(spans)
+        })
+        .collect();
+    Paragraph::new(lines).alignment(Alignment::Center)
+}
+
+/// How many lines a glyph of the [ascii](super::ClockConfig::ascii) clock is high
+pub const ASCII_HEIGHT: u16 = 5;
+
+/// The border of the [ascii](super::ClockConfig::ascii) clock
+pub const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// The glyph for `c` in the big font of the [ascii](super::ClockConfig::ascii) clock, if it has one
+#[must_use]
+pub const fn ascii_glyph(c: char) -> Option<[&'static str; ASCII_HEIGHT as usize]> {
+    Some(match c.to_ascii_uppercase() {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", "###", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        'A' => ["###", "# #", "###", "# #", "# #"],
+        'P' => ["###", "# #", "###", "#  ", "#  "],
+        'M' => ["# #", "###", "###", "# #", "# #"],
+        ':' => [" ", "#", " ", "#", " "],
+        '.' => [" ", " ", " ", " ", "#"],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        // the same width as the colon, so that blinking does not move the digits
+        ' ' => [" ", " ", " ", " ", " "],
+        _ => return None,
+    })
+}
+
+/// The time in big letters made of `#`, for terminals that can't show the block characters
+///
+/// Characters without an [ascii glyph](ascii_glyph) are shown small in the middle.
+#[must_use]
+pub fn asciiw<'a>(ftime: &str, color: Color) -> Paragraph<'a> {
+    let lines: Vec<Line> = (0..usize::from(ASCII_HEIGHT))
+        .map(|row| {
+            ftime
+                .chars()
+                .map(|c| match ascii_glyph(c) {
+                    Some(glyph) => glyph[row].to_string(),
+                    None if row == usize::from(ASCII_HEIGHT) / 2 => c.to_string(),
+                    None => " ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+                .into()
+        })
+        .collect();
+    Paragraph::new(lines)
+        .style(Style::new().fg(color))
+        .alignment(Alignment::Center)
+}
+
+/// A timebar like `50% [#####-----]` that is `width` wide, for the
+/// [ascii](super::ClockConfig::ascii) clock
+#[must_use]
+#[allow(
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss
+)]
+pub fn ascii_barw<'a>(ratio: f64, width: u16, style: Style) -> Paragraph<'a> {
+    let ratio = ratio.clamp(0.0, 1.0);
+    let label = format!("{:.0}% ", ratio * 100.0);
+    let len = usize::from(width).saturating_sub(label.len() + 2);
+    let filled = (ratio * len as f64).round() as usize;
+    Paragraph::new(Line::from(vec![
+        Span::raw(label),
+        Span::raw("["),
+        Span::styled("#".repeat(filled), style),
+        Span::raw("-".repeat(len - filled)),
+        Span::raw("]"),
+    ]))
+}
+
+/// How full the timebar was over the last seconds, one bar for each second of the `history`
+#[must_use]
+pub fn sparklinew<'a>(clock: &Clock, history: &'a [u64], padding: u16) -> Sparkline<'a> {
+    Sparkline::default()
+        .data(history)
+        .max(100)
+        .style(
+            Style::default().fg(clock
+                .custom_color
+                .unwrap_or_else(|| clock.config.palette.bar())),
+        )
+        .block(Block::default().padding(Padding::right(padding)))
+}
+
+/// One of the [stacked timebars](Clock::stacked_bars) and its label, which is `ratio` full
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)] // at most a day
+pub fn stacked_barw<'a>(
+    clock: &Clock,
+    len: TimeBarLength,
+    ratio: f64,
+    padding: u16,
+) -> (LineGauge<'a>, Paragraph<'a>) {
+    let gauge = LineGauge::default()
+        .filled_style(Style::default().fg(bar_color(clock, ratio)))
+        .unfilled_style(Style::default())
+        .block(Block::default().padding(Padding::right(padding)))
+        .ratio(ratio.clamp(0.0, 1.0));
+    let elapsed = (ratio * len.as_secs() as f64) as u64;
+    let mut text = format!(
+        "{} / {len}",
+        humantime::Duration::from(std::time::Duration::from_secs(elapsed))
+    );
+    if clock.config.percent {
+        let _ = write!(text, " ({:.0}%)", ratio * 100.0);
+    }
+    let label = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(Block::default().padding(Padding::right(padding)));
+    (gauge, label)
+}
+
+/// How much of the timebar is filled when it is `ratio` full, the
+/// [drained](super::ClockConfig::drain) timebar empties instead
+///
+/// The timer is never full, so it does not drain either.
+fn filled_ratio(clock: &Clock, ratio: f64) -> f64 {
+    if clock.config.drain && clock.timebar_len() != Some(TimeBarLength::Timer) {
+        1.0 - ratio
     } else {
-        None
+        ratio
     }
 }
 
+/// How the filled part of the timebar looks, depending on how far along it is
+///
+/// The [blinking](Clock::timebar_blinks) timebar is dimmed every other half second, for the
+/// terminals that don't blink by themselves.
+fn timebar_style(clock: &Clock, data: &Data, ratio: f64) -> Style {
+    let style = if clock.reached_end(*data.now()) {
+        Style::default()
+            .slow_blink()
+            .bold()
+            .underlined()
+            .fg(clock.config.palette.done())
+            .crossed_out()
+    } else if nearly_done(clock, *data.now()) {
+        Style::default()
+            .slow_blink()
+            .bold()
+            .fg(clock.config.palette.warning())
+    } else {
+        return Style::default().fg(bar_color(clock, ratio));
+    };
+    if u128::from(data.now().timestamp_subsec_millis()) >= super::BLINK_RATE.as_millis() {
+        style.remove_modifier(Modifier::BOLD).fg(Color::DarkGray)
+    } else {
+        style
+    }
+}
+
+/// The color of a timebar that is `ratio` full
+fn bar_color(clock: &Clock, ratio: f64) -> Color {
+    if clock.config.gradient {
+        clock.config.palette.gradient(ratio)
+    } else {
+        clock
+            .custom_color
+            .unwrap_or_else(|| clock.config.palette.bar())
+    }
+}
+
+/// Is less time than the [`warn_threshold`](Clock::warn_threshold) left?
+///
+/// Only for the timebars that count down to something, the minute, hour and day just go on.
+pub(crate) fn nearly_done(clock: &Clock, current_time: DateTime<Local>) -> bool {
+    let Some(threshold) = clock.config.warn_threshold else {
+        return false;
+    };
+    match (clock.timebar_len(), clock.timebar_remaining(current_time)) {
+        (
+            Some(
+                TimeBarLength::Countup(_)
+                | TimeBarLength::Alarm(_)
+                | TimeBarLength::Until(_)
+                | TimeBarLength::Custom(_)
+                | TimeBarLength::Pomodoro { .. }
+                | TimeBarLength::Interval(_),
+            ),
+            Some(remaining),
+        ) => (remaining as u64) < threshold.as_secs(),
+        _ => false,
+    }
+}
+
+/// Go from green over yellow to red as the `ratio` goes from 0.0 to 1.0
+#[must_use]
+#[allow(clippy::cast_possible_truncation)] // clamped to the range of u8
+pub fn gradient_color(ratio: f64) -> Color {
+    let ratio = ratio.clamp(0.0, 1.0);
+    let red = (510.0 * ratio).min(255.0) as u8;
+    let green = (510.0 * (1.0 - ratio)).min(255.0) as u8;
+    Color::Rgb(red, green, 0)
+}
+
+/// The timebar itself, if there is one
+///
+/// There is none before the [setup](Clock::setup) or as long as `data` has no timebar ratio.
+#[must_use]
+pub fn timebarw<'a>(
+    clock: &Clock,
+    data: &Data,
+    timebarw_padding: &[u16],
+    inner_rect: Rect,
+) -> Option<TimeBarWidget<'a>> {
+    clock.timebar_len()?;
+    clock.last_reset?;
+    debug!("time bar ration: {:?}", data.timebar_ratio());
+    let ratio = data.timebar_ratio()?;
+    let block = Block::default().padding(Padding::right(if inner_rect.width > 80 {
+        timebarw_padding[0]
+    } else {
+        timebarw_padding[1]
+    }));
+
+    let timebarw = match clock.config.bar_style {
+        BarStyle::Line => TimeBarWidget::Line(
+            LineGauge::default()
+                .filled_style(timebar_style(clock, data, ratio))
+                .unfilled_style(Style::default())
+                .block(block)
+                .ratio(filled_ratio(clock, ratio)),
+        ),
+        BarStyle::Block => {
+            let gauge = Gauge::default()
+                .gauge_style(timebar_style(clock, data, ratio))
+                .block(block)
+                .ratio(filled_ratio(clock, ratio).clamp(0.0, 1.0));
+            // the gauge shows the percentage unless it gets another label
+            TimeBarWidget::Block(if clock.config.percent {
+                gauge
+            } else {
+                gauge.label("")
+            })
+        }
+    };
+    Some(timebarw)
+}
+
+/// The text below the timebar, like how much time has passed and when it is full
+///
+/// There is none before the [setup](Clock::setup), as the timebar has not started yet.
+#[must_use]
 pub fn timebarw_label<'a>(
     clock: &Clock,
     data: &Data,
     timebarw_padding: &[u16],
     inner_rect: Rect,
 ) -> Option<Paragraph<'a>> {
-    clock.timebar_len().map(|len| {
-        let last_reset = clock.last_reset.unwrap().round_subsecs(0);
-        let time_now = match clock.timebar_len().unwrap() {
-            TimeBarLength::Countup(secs) => {
-                if clock.did_notify {
-                    humantime::Duration::from(chrono::Duration::seconds(secs).to_std().unwrap())
-                } else {
-                    humantime::Duration::from(
-                        data.now()
-                            .round_subsecs(0)
-                            .signed_duration_since(last_reset)
-                            .to_std()
-                            .unwrap(),
-                    )
+    clock
+        .timebar_len()
+        .zip(clock.last_reset)
+        .map(|(len, last_reset)| {
+            let last_reset = clock.in_timezone(last_reset).trunc_subsecs(0);
+            let mut text = timebar_label_text(clock, data, len, last_reset);
+            if let Some(lap) = clock
+                .current_lap(*data.now())
+                .filter(|_| len == TimeBarLength::Timer && !clock.laps().is_empty())
+            {
+                let _ = write!(
+                    text,
+                    " | lap {} + {}",
+                    clock.laps().len() + 1,
+                    format_duration(lap)
+                );
+            }
+            if let Some(ratio) = data.timebar_ratio().filter(|_| clock.config.percent) {
+                if len != TimeBarLength::Timer {
+                    let _ = write!(text, " ({:.0}%)", ratio * 100.0);
                 }
             }
-            TimeBarLength::Hour => humantime::Duration::from(
-                data.now()
-                    .signed_duration_since(last_reset)
+            if clock.config.alarm.len() > clock.alarm_idx + 1 {
+                let _ = write!(
+                    text,
+                    " | alarms left: {}",
+                    clock.config.alarm.len() - clock.alarm_idx - 1
+                );
+            }
+            if clock.config.repeat {
+                let _ = write!(text, " | round {}", clock.repeat_round + 1);
+            }
+            if !clock.snoozed.is_zero() {
+                text.push_str(" (snoozed)");
+            }
+            // the timer is a stopwatch, which stops instead of pausing
+            let stopped = clock.paused && len == TimeBarLength::Timer;
+            if stopped {
+                text.push_str(" (stopped)");
+            } else if clock.paused {
+                text.push_str(" (paused)");
+            }
+            // without colors, the words have to say it
+            if clock.config.palette == Palette::Mono {
+                if clock.reached_end(*data.now()) {
+                    text.push_str(" (done)");
+                } else if nearly_done(clock, *data.now()) {
+                    text.push_str(" (almost done)");
+                }
+            }
+
+            let mut line = Line::from(text);
+            if let Some(overtime) = timebar_overtime(clock, data) {
+                line.push_span(Span::raw(" "));
+                line.push_span(Span::styled(
+                    format!("+{}", humantime::Duration::from(overtime)),
+                    Style::new().fg(clock.config.palette.warning()).bold(),
+                ));
+            }
+
+            Paragraph::new(line)
+                .style(if stopped {
+                    Style::new().dim()
+                } else {
+                    Style::new()
+                })
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default().padding(Padding::right(if inner_rect.width > 80 {
+                        timebarw_padding[0]
+                    } else {
+                        timebarw_padding[1]
+                    })),
+                )
+        })
+}
+
+/// Show `secs` roughly, with only the two largest units, like "12 days 4h" or "3m 20s"
+#[must_use]
+pub fn coarse_duration(secs: i64) -> String {
+    duration_units(secs, 2)
+}
+
+/// Show `secs` with all units that are not zero, like "76 days 12h 41m 14s"
+#[must_use]
+pub fn exact_duration(secs: i64) -> String {
+    duration_units(secs, 4)
+}
+
+/// Show `secs` with at most `count` units, starting at the largest one that is not zero
+fn duration_units(secs: i64, count: usize) -> String {
+    let units = [
+        (
+            secs / 86400,
+            if secs / 86400 == 1 { " day" } else { " days" },
+        ),
+        (secs / 3600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let first = units
+        .iter()
+        .position(|(n, _)| *n > 0)
+        .unwrap_or(units.len() - 1);
+    units[first..]
+        .iter()
+        .take(count)
+        .filter(|(n, _)| *n > 0 || first == units.len() - 1)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Show `secs` rounded to its largest unit, like "about 1 hour" or "3 days"
+#[must_use]
+pub fn fuzzy_duration(secs: i64) -> String {
+    // round to the nearest unit, 90 minutes are already 2 hours
+    let round = |unit: i64| (secs + unit / 2) / unit;
+    let (n, unit) = if secs < 60 {
+        return "less than a minute".to_string();
+    } else if round(60) < 60 {
+        (round(60), "minute")
+    } else if round(3600) < 24 {
+        (round(3600), "hour")
+    } else {
+        (round(86400), "day")
+    };
+    if n == 1 {
+        format!("about 1 {unit}")
+    } else {
+        format!("{n} {unit}s")
+    }
+}
+
+/// How long until the date of the [Until](TimeBarLength::Until) timebar, roughly
+fn timebar_until_left(clock: &Clock, current_time: DateTime<Local>) -> String {
+    match clock.timebar_remaining(current_time) {
+        // the countdown to the new year is more fun to the second
+        Some(remaining) if remaining > 0 && clock.config.new_year => {
+            format!("{} left", exact_duration(remaining))
+        }
+        Some(remaining) if remaining > 0 => format!("{} left", coarse_duration(remaining)),
+        _ => "elapsed".to_string(),
+    }
+}
+
+/// The date the [Until](TimeBarLength::Until) timebar counts down to, with the time unless it
+/// is midnight
+fn timebar_until_date(clock: &Clock) -> String {
+    let until = clock.in_timezone(clock.config.until.unwrap());
+    if until.time() == chrono::NaiveTime::MIN {
+        clock.localized(&until, clock.date_format())
+    } else {
+        clock.localized(
+            &until,
+            &format!("{} {}", clock.date_format(), clock.time_format_short()),
+        )
+    }
+}
+
+/// When the timebar will be full, on the wall clock of the chosen timezone, see
+/// [`timebar_eta`](Clock::timebar_eta)
+///
+/// For the [Timer](TimeBarLength::Timer), which is never full, this is when it started. Nothing
+/// before the [setup](Clock::setup).
+#[must_use]
+pub fn timebar_until(clock: &Clock, data: &Data) -> Option<DateTime<FixedOffset>> {
+    Some(clock.in_timezone(clock.timebar_eta(data).or(clock.last_reset)?))
+}
+
+/// The line below the date with [`show_eta`](Clock::show_eta), with the date if it is not today
+fn eta_text(clock: &Clock, data: &Data) -> Option<String> {
+    let eta = clock.in_timezone(clock.timebar_eta(data)?);
+    let format = if eta.date_naive() == clock.in_timezone(*data.now()).date_naive() {
+        clock.time_format().to_owned()
+    } else {
+        format!("{} {}", clock.date_format(), clock.time_format_short())
+    };
+    Some(format!("done at {}", clock.localized(&eta, &format)))
+}
+
+/// How long the countdown or alarm is already over, if we show the overtime
+fn timebar_overtime(clock: &Clock, data: &Data) -> Option<std::time::Duration> {
+    if !clock.config.overtime || !clock.is_finished() {
+        return None;
+    }
+    data.now()
+        .signed_duration_since(clock.timebar_eta(data)?)
+        .to_std()
+        .ok()
+}
+
+/// The first part of the [label of the timebar](timebarw_label), which differs for each timebar
+fn timebar_label_text(
+    clock: &Clock,
+    data: &Data,
+    len: TimeBarLength,
+    last_reset: DateTime<FixedOffset>,
+) -> String {
+    let time_now = timebar_time_now(clock, data, len, last_reset);
+    // the label is only made once the timebar started, so there always is an end
+    let until_time = timebar_until(clock, data).unwrap_or(last_reset);
+    let until = until_time.format(clock.boundary_format());
+    match len {
+        TimeBarLength::Timer => {
+            let started_at = clock.in_timezone(data.started_at);
+            // a timer started on another day needs the date to make sense
+            if started_at.date_naive() == clock.in_timezone(*data.now()).date_naive() {
+                format!("{} + {time_now}", started_at.format(clock.time_format()))
+            } else {
+                format!(
+                    "{} {} + {time_now}",
+                    started_at.format(clock.date_format()),
+                    started_at.format(clock.time_format())
+                )
+            }
+        }
+        TimeBarLength::Pomodoro { .. } => format!(
+            "{} {} | {time_now} / {} | {} -> {until}",
+            if clock.pomodoro_on_break {
+                "Break"
+            } else {
+                "Work"
+            },
+            clock.pomodoro_round + 1,
+            humantime::Duration::from(
+                chrono::Duration::seconds(len.phase_secs(clock.pomodoro_on_break))
                     .to_std()
-                    .unwrap(),
+                    .unwrap()
             ),
-            _ => humantime::Duration::from(
-                data.now()
-                    .round_subsecs(0)
-                    .signed_duration_since(last_reset)
+            last_reset.format(clock.time_format_short())
+        ),
+        TimeBarLength::Interval(_) => {
+            let step = clock.interval_step().unwrap();
+            format!(
+                "{} {}/{} | {time_now} / {len} | {} -> {until}",
+                step.name,
+                step.round,
+                clock.config.intervals.as_ref().map_or(1, |i| i.rounds),
+                last_reset.format(clock.time_format_short())
+            )
+        }
+        TimeBarLength::Until(_) => format!(
+            "{} | {} -> {}",
+            timebar_until_left(clock, *data.now()),
+            last_reset.format(clock.time_format_short()),
+            timebar_until_date(clock)
+        ),
+        // they start and end at midnight, so only the date of the end says something
+        _ if len.is_calendar() => format!(
+            "{time_now} / {len} -> {}",
+            clock.localized(&until_time, clock.date_format())
+        ),
+        _ => format!(
+            "{time_now} / {len} | {} -> {until}",
+            last_reset.format(clock.boundary_format())
+        ),
+    }
+}
+
+/// How much time has passed or is left, depending on [`show_remaining`](Clock::show_remaining)
+fn timebar_time_now(
+    clock: &Clock,
+    data: &Data,
+    len: TimeBarLength,
+    last_reset: DateTime<FixedOffset>,
+) -> String {
+    // the week, month and year are too long to show to the second
+    match clock.timebar_remaining(*data.now()) {
+        Some(remaining) if clock.config.show_remaining && clock.config.fuzzy => {
+            format!("{} left", fuzzy_duration(remaining))
+        }
+        _ if clock.config.fuzzy => fuzzy_duration(
+            timebar_elapsed(clock, data, len, last_reset)
+                .as_secs()
+                .try_into()
+                .unwrap_or(i64::MAX),
+        ),
+        Some(remaining) if clock.config.show_remaining && len.is_calendar() => {
+            format!("{} left", coarse_duration(remaining))
+        }
+        _ if len.is_calendar() => {
+            coarse_duration(data.now().signed_duration_since(last_reset).num_seconds())
+        }
+        Some(remaining) if clock.config.show_remaining => format!(
+            "{} left",
+            humantime::Duration::from(std::time::Duration::from_secs(remaining as u64))
+        ),
+        _ => timebar_elapsed(clock, data, len, last_reset).to_string(),
+    }
+}
+
+/// How much time of the timebar has passed, for showing it in the label
+fn timebar_elapsed(
+    clock: &Clock,
+    data: &Data,
+    len: TimeBarLength,
+    last_reset: DateTime<FixedOffset>,
+) -> humantime::Duration {
+    match len {
+        TimeBarLength::Countup(secs) | TimeBarLength::Alarm(secs) => {
+            if clock.did_notify {
+                humantime::Duration::from(
+                    chrono::Duration::seconds(secs).to_std().unwrap_or_default(),
+                )
+            } else {
+                let now = data.now().trunc_subsecs(0);
+                humantime::Duration::from(
+                    (now.signed_duration_since(last_reset) - clock.paused_duration(now))
+                        .to_std()
+                        .unwrap_or_default(),
+                )
+            }
+        }
+        // the clock may have been set back to before the last reset
+        TimeBarLength::Hour => humantime::Duration::from(
+            data.now()
+                .signed_duration_since(last_reset)
+                .to_std()
+                .unwrap_or_default(),
+        ),
+        _ => {
+            // the pause may have started on a whole second, like for the stopped timer
+            let now = data.now().trunc_subsecs(0);
+            humantime::Duration::from(
+                (now.signed_duration_since(last_reset) - clock.paused_duration(now))
                     .to_std()
-                    .unwrap(),
-            ),
+                    .unwrap_or_default(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::clock::tests::{fixed_now, parse, parse_at, utc};
+
+    /// Render the clock with `data` into a buffer of `width` and `height`
+    fn render(clock: &Clock, data: &Data, width: u16, height: u16) -> Buffer {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        ClockWidget::new(clock, data).render(area, &mut buf);
+        buf
+    }
+
+    /// Render a single frame of the clock with the command line `args` at the fixed time
+    fn render_args(args: &[&str], width: u16, height: u16) -> String {
+        parse_at(std::iter::once(&"some exec").chain(args), fixed_now())
+            .render_once(width, height)
+            .unwrap()
+    }
+
+    #[test]
+    fn the_label_shows_when_the_timebar_is_full() {
+        let until = |args: &[&str], last_reset: &str| {
+            let mut c = parse(args);
+            c.config.timezone = Some(chrono_tz::UTC);
+            c.last_reset = Some(utc(last_reset));
+            let mut data = Data::new(c.timebar_len());
+            data.update(utc(last_reset), String::new(), String::new(), None);
+            timebar_until(&c, &data)
+                .unwrap()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
         };
-        let until = {
-            // we need to cut off the seconds if we're not in custom and countup mode, otherwise,
-            // the timestamp will not be correct. This fixes #17
-            match len {
-                TimeBarLength::Custom(_) | TimeBarLength::Countup(_) => last_reset,
-                _ => last_reset.with_second(0).unwrap(),
-            }
-        }
-        // BUG: seconds are sometimes a little too much, for
-        // example with `-o` #17
-        .checked_add_signed(len.into())
-        .expect("could not calculate when the countdown finishes")
-        .format(TIME_FORMAT_SHORT);
-
-        let text: String = match clock.timebar_len().unwrap() {
-            TimeBarLength::Timer => format!("{} + {time_now}", data.started_at.format(TIME_FORMAT)),
-            TimeBarLength::Countup(_) | TimeBarLength::Custom(_) => format!(
-                "{time_now} / {len} | {} -> {until}",
-                last_reset.format(TIME_FORMAT_SHORT)
+        for (args, last_reset, expected) in [
+            (&["-m"][..], "2024-07-20 13:37:00", "2024-07-20 13:38:00"),
+            (&["-o"], "2024-07-20 13:00:00", "2024-07-20 14:00:00"),
+            (&["-o"], "2024-07-20 23:00:00", "2024-07-21 00:00:00"),
+            (&["-d"], "2024-07-20 00:00:00", "2024-07-21 00:00:00"),
+            (&["-u", "90s"], "2024-07-20 13:59:31", "2024-07-20 14:01:01"),
+        ] {
+            let args = std::iter::once(&"some exec")
+                .chain(args)
+                .copied()
+                .collect::<Vec<_>>();
+            assert_eq!(until(&args, last_reset), expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn nothing_has_passed_before_the_last_reset() {
+        for args in [&["-o"][..], &["-u", "5m"], &["-t"]] {
+            let mut c = parse(std::iter::once(&"some exec").chain(args));
+            c.last_reset = Some(utc("2024-07-20 14:00:00"));
+            let mut data = Data::new(c.timebar_len());
+            data.update(fixed_now(), String::new(), String::new(), None);
+            let len = c.timebar_len().unwrap();
+            let last_reset = c.last_reset.unwrap().fixed_offset();
+            assert_eq!(
+                timebar_elapsed(&c, &data, len, last_reset).to_string(),
+                "0s",
+                "{args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_label_shows_the_seconds_of_the_phase() {
+        let frame = render_args(&["-m", "--phase", "30s", "--tz", "UTC"], 100, 14);
+        assert!(frame.contains("13:37:30 -> 13:38:30"), "{frame}");
+        let frame = render_args(&["-o", "--phase", "30m", "--tz", "UTC"], 100, 14);
+        assert!(frame.contains("| 13:30 -> 14:30"), "{frame}");
+    }
+
+    #[test]
+    fn durations() {
+        // coarse durations only show the largest units
+        assert_eq!(coarse_duration(12 * 86400 + 4 * 3600 + 59), "12 days 4h");
+        assert_eq!(coarse_duration(86400 + 30), "1 day");
+        assert_eq!(coarse_duration(3 * 60 + 20), "3m 20s");
+        assert_eq!(coarse_duration(0), "0s");
+        // exact durations show all units
+        assert_eq!(
+            exact_duration(76 * 86400 + 12 * 3600 + 41 * 60 + 14),
+            "76 days 12h 41m 14s"
+        );
+        assert_eq!(exact_duration(3 * 60 + 20), "3m 20s");
+        // fuzzy durations are rounded to the largest unit
+        assert_eq!(fuzzy_duration(59), "less than a minute");
+        assert_eq!(fuzzy_duration(3 * 60 + 20), "3 minutes");
+        assert_eq!(fuzzy_duration(59 * 60 + 40), "about 1 hour");
+        assert_eq!(fuzzy_duration(3600 + 23 * 60 + 4), "about 1 hour");
+        assert_eq!(fuzzy_duration(3600 + 30 * 60), "2 hours");
+        assert_eq!(fuzzy_duration(3 * 86400), "3 days");
+    }
+
+    #[test]
+    fn only_strip_the_leading_zero_of_the_hour() {
+        assert_eq!(strip_leading_zero("09:05:03"), "9:05:03");
+        assert_eq!(strip_leading_zero("00:05"), "0:05");
+        assert_eq!(strip_leading_zero("12:05:03"), "12:05:03");
+        assert_eq!(strip_leading_zero("0:05"), "0:05");
+    }
+
+    #[test]
+    fn binary_clock_bits() {
+        let bits = binary_bits("13:37:00".parse().unwrap());
+        let (o, i) = (false, true);
+        assert_eq!(
+            bits,
+            [
+                [o, o, o, i],
+                [o, o, i, i],
+                [o, o, i, i],
+                [o, i, i, i],
+                [o, o, o, o],
+                [o, o, o, o],
+            ]
+        );
+    }
+
+    #[test]
+    fn the_data_has_the_latest_ratio() {
+        let mut data = Data::new(None);
+        for ratio in [0.1, 0.2, 0.3] {
+            data.update(
+                fixed_now(),
+                "date".to_owned(),
+                "time".to_owned(),
+                Some(ratio),
+            );
+            assert_eq!(data.timebar_ratio(), Some(ratio));
+        }
+    }
+
+    #[test]
+    fn no_timebar_before_the_setup() {
+        for args in [
+            &["-u", "5m"][..],
+            &["-t", "--big-countdown"],
+            &["--pomodoro"],
+            &["-m"],
+            &["--alarm", "14:00", "--show-eta"],
+            &["--intervals", "work:40s,rest:20s x2"],
+            &["--all-bars"],
+        ] {
+            let c = parse(std::iter::once(&"some exec").chain(args));
+            let mut data = Data::new(c.timebar_len());
+            data.update(fixed_now(), String::new(), String::new(), None);
+            let frame = buffer_to_string(&render(&c, &data, 100, 20));
+            assert!(
+                !frame.contains("0%") && !frame.contains("->"),
+                "{args:?}\n{frame}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_binary_clock_shows_the_time_of_the_frame() {
+        // the time may have gone on since the data of the frame was made
+        let frame = |clock_now| {
+            let c = parse_at(["some exec", "--binary", "--tz", "UTC"], clock_now);
+            let mut data = Data::new(c.timebar_len());
+            data.update(fixed_now(), String::new(), String::new(), None);
+            render(&c, &data, 80, 20)
+        };
+        assert_eq!(
+            frame(fixed_now()),
+            frame(fixed_now() + chrono::Duration::minutes(5))
+        );
+    }
+
+    #[test]
+    fn the_world_clock_shows_the_time_of_the_frame() {
+        let frame = |clock_now| {
+            let c = parse_at(["some exec", "--world", "UTC,Asia/Tokyo"], clock_now);
+            let mut data = Data::new(c.timebar_len());
+            data.update(fixed_now(), String::new(), String::new(), None);
+            render(&c, &data, 100, 20)
+        };
+        assert_eq!(
+            frame(fixed_now()),
+            frame(fixed_now() + chrono::Duration::minutes(5))
+        );
+    }
+
+    #[test]
+    fn the_gradient_goes_from_green_to_red() {
+        assert_eq!(gradient_color(0.0), Color::Rgb(0, 255, 0));
+        assert_eq!(gradient_color(0.5), Color::Rgb(255, 255, 0));
+        assert_eq!(gradient_color(1.0), Color::Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn the_palettes_pick_their_colors() {
+        assert_eq!(Palette::Deuteranopia.gradient(0.0), Color::Rgb(0, 114, 178));
+        assert_eq!(Palette::Deuteranopia.gradient(1.0), Color::Rgb(230, 159, 0));
+        assert_eq!(Palette::Mono.gradient(0.5), Color::Reset);
+    }
+
+    #[test]
+    fn the_history_of_the_timebar_is_kept_for_the_sparkline() {
+        // only a new second goes into the history
+        let mut data = Data::new(None);
+        for (time, ratio) in [("1", 0.1), ("1", 0.15), ("2", 0.2), ("3", 1.5)] {
+            data.update(fixed_now(), "date".to_owned(), time.to_owned(), Some(ratio));
+        }
+        assert_eq!(data.history(3), [10, 20, 100]);
+        assert_eq!(data.history(10_000).len(), HISTORY_LEN);
+    }
+
+    #[test]
+    fn render_the_phase_of_the_moon() {
+        let frame = render_args(&["--moon"], 100, 20);
+        assert!(
+            frame.contains(MoonPhase::at(&fixed_now()).name()),
+            "{frame}"
+        );
+    }
+
+    #[test]
+    fn render_when_the_timebar_is_full() {
+        let frame = render_args(&["-c", "5m", "--show-eta"], 100, 20);
+        assert!(frame.contains("done at "), "{frame}");
+    }
+
+    #[test]
+    fn fall_back_to_a_small_clock_in_tiny_terminals() {
+        for (width, height) in [(20, 6), (80, 8), (3, 3), (0, 0)] {
+            let frame = render_args(&["-c", "5m"], width, height);
+            assert_eq!(frame.lines().count(), usize::from(height));
+            if width > 0 {
+                assert!(frame.contains(':') || width < 10, "{frame}");
+            }
+        }
+        let frame = render_args(&[], 30, 10);
+        assert!(frame.contains("too small"), "{frame}");
+    }
+
+    #[test]
+    fn the_drained_timebar_starts_full() {
+        for (drain, percent) in [(false, "0%"), (true, "100%")] {
+            let mut args = vec!["-u", "5m", "--ascii"];
+            if drain {
+                args.push("--drain");
+            }
+            let frame = render_args(&args, 100, 20);
+            assert!(frame.contains(&format!(" {percent} [")), "{frame}");
+        }
+    }
+
+    #[test]
+    fn render_fuzzy_durations() {
+        let mut c = parse_at(["some exec", "-t", "--fuzzy"], fixed_now());
+        c.setup().unwrap();
+        let mut data = Data::new(c.timebar_len());
+        let now = c.now();
+        data.set_started_at(now - chrono::Duration::minutes(83));
+        c.last_reset = Some(now - chrono::Duration::minutes(83));
+        data.update(now, String::new(), String::new(), c.timebar_ratio(now));
+        assert!(buffer_to_string(&render(&c, &data, 100, 20)).contains("+ about 1 hour"));
+    }
+
+    #[test]
+    fn render_a_single_frame() {
+        let frame = render_args(&["-m"], 60, 20);
+        assert_eq!(frame.lines().count(), 20);
+        assert!(frame.lines().next().unwrap().contains("crock"));
+        assert!(frame.contains(" / 1m | "));
+    }
+
+    #[test]
+    fn render_without_the_border() {
+        let frame = render_args(&["--no-border"], 60, 20);
+        assert!(!frame.contains('│') && !frame.contains("crock"));
+    }
+
+    #[test]
+    fn render_with_a_custom_title() {
+        let frame = render_args(&["--title", "Kitchen Timer", "--no-version"], 60, 20);
+        assert!(frame.lines().next().unwrap().contains("Kitchen Timer"));
+        assert!(!frame.contains(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn render_the_stacked_timebars() {
+        let frame = render_args(&["--all-bars"], 100, 20);
+        for len in [" / 1m", " / 1h", " / 1day"] {
+            assert!(frame.contains(len), "{frame}");
+        }
+    }
+
+    #[test]
+    fn render_with_custom_padding() {
+        let frame = render_args(&["--padding", "0"], 100, 20);
+        assert!(frame.lines().nth(1).unwrap().contains('-'), "{frame}");
+        let frame = render_args(&["--padding", "1000"], 100, 20);
+        assert_eq!(frame.lines().count(), 20);
+    }
+
+    #[test]
+    fn render_the_time_since_a_date() {
+        let frame = render_args(&["--since", "2020-01-01"], 100, 20);
+        assert!(frame.contains("2020-01-01 00:00:00 + "), "{frame}");
+        assert!(frame.contains("years"), "{frame}");
+    }
+
+    #[test]
+    fn render_each_font_even_if_it_does_not_fit() {
+        for font in ["full", "half", "quadrant", "sextant"] {
+            for (width, height) in [(100, 20), (12, 4)] {
+                let frame = render_args(&["--font", font], width, height);
+                assert_eq!(frame.lines().count(), usize::from(height));
+            }
+        }
+    }
+
+    #[test]
+    fn render_the_timebar_as_a_line_and_as_a_block() {
+        for (style, line) in [("line", true), ("block", false)] {
+            let frame = render_args(&["-c", "5m", "--bar-style", style], 100, 20);
+            assert_eq!(frame.contains("0% ─"), line, "{frame}");
+        }
+    }
+
+    #[test]
+    fn render_only_ascii_characters() {
+        for args in [&["-c", "5m"][..], &["--all-bars"], &["-T", "--with-utc"]] {
+            let args = std::iter::once(&"--ascii")
+                .chain(args)
+                .copied()
+                .collect::<Vec<_>>();
+            let frame = render_args(&args, 100, 20);
+            assert!(frame.is_ascii(), "{frame}");
+        }
+        assert_eq!(ascii_glyph('1'), Some([" # ", "## ", " # ", " # ", "###"]));
+    }
+
+    #[test]
+    fn flash_the_screen_by_inverting_it() {
+        let mut c = parse_at(["some exec", "-c", "5m", "--flash"], fixed_now());
+        c.setup().unwrap();
+        let mut data = Data::new(c.timebar_len());
+        let now = c.now();
+        data.update(now, String::new(), String::new(), c.timebar_ratio(now));
+        for (frames, inverted) in [(6, true), (5, false), (0, false)] {
+            c.flash_frames = frames;
+            assert_eq!(c.flash_inverted(), inverted);
+            let buf = render(&c, &data, 40, 10);
+            assert_eq!(
+                buf.get(0, 0).modifier.contains(Modifier::REVERSED),
+                inverted
+            );
+        }
+    }
+
+    #[test]
+    fn the_chess_clock_shows_both_players() {
+        let frame = render_args(&["--chess", "5m", "--ascii"], 100, 20);
+        assert!(
+            frame.contains("Player 1") && frame.contains("Player 2"),
+            "{frame}"
+        );
+        assert_eq!(frame.matches("5:00").count(), 2, "{frame}");
+    }
+
+    #[test]
+    fn without_colors_the_label_says_that_the_timebar_is_done() {
+        let mut c = parse_at(
+            ["some exec", "-u", "1s", "--palette", "mono", "--json"],
+            fixed_now(),
+        );
+        c.setup().unwrap();
+        let now = c.last_reset.unwrap() + chrono::Duration::seconds(1);
+        let mut data = Data::new(c.timebar_len());
+        data.update(now, "date".to_owned(), "12:00:00".to_owned(), Some(1.0));
+        assert!(buffer_to_string(&render(&c, &data, 100, 20)).contains("(done)"));
+
+        let mut c = parse_at(["some exec", "--no-color", "--chess", "1m"], fixed_now());
+        c.setup().unwrap();
+        assert!(render(&c, &data, 100, 20)
+            .content
+            .iter()
+            .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+    }
+
+    /// Render the clock with the command line `args` at the fixed time, into a terminal that is
+    /// always the same size
+    fn snapshot(args: &[&str], last_reset: &str) -> String {
+        let now = fixed_now();
+        let mut c = parse_at(["some exec", "--tz", "UTC"].iter().chain(args), now);
+        c.setup().unwrap();
+        c.last_reset = Some(utc(last_reset));
+        let mut data = Data::new(c.timebar_len());
+        data.set_started_at(utc(last_reset));
+        let wall_time = c.in_timezone(now);
+        data.update(
+            now,
+            c.fdate(wall_time),
+            wall_time.format(c.time_format()).to_string(),
+            c.timebar_ratio(now),
+        );
+        let mut terminal = Terminal::new(TestBackend::new(100, 14)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(ClockWidget::new(&c, &data), frame.size()))
+            .unwrap();
+        buffer_to_string(terminal.backend().buffer())
+    }
+
+    #[test]
+    fn snapshot_of_the_minute() {
+        // the whole frame, without the version at the bottom
+        let frame = snapshot(&["-m"], "2024-07-20 13:37:00");
+        assert_eq!(
+            frame.lines().take(13).collect::<Vec<_>>(),
+            [
+                "┌──────────────────────────────────────────────crock───────────────────────────────────────────────┐",
+                "│                     2024-07-20  50% ──────────────────────────────                               │",
+                "│                                      30s / 1m | 13:37 -> 13:38                                   │",
+                "│                                                                                                  │",
+                "│                   ██     ████            ████   ██████           ████    █████                   │",
+                "│                  ███    ██  ██    ██    ██  ██  ██  ██    ██    ██  ██  ██   ██                  │",
+                "│                   ██        ██    ██        ██      ██    ██        ██  ██  ███                  │",
+                "│                   ██      ███             ███      ██             ███   ██ ████                  │",
+                "│                   ██        ██              ██    ██                ██  ████ ██                  │",
+                "│                   ██    ██  ██    ██    ██  ██    ██      ██    ██  ██  ███  ██                  │",
+                "│                 ██████   ████     ██     ████     ██      ██     ████    █████                   │",
+                "│                                                                                                  │",
+                "│        UTC+00:00                                                                                 │",
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshots_of_the_timebars() {
+        for (args, last_reset, expected) in [
+            (
+                &["-m"][..],
+                "2024-07-20 13:37:00",
+                [
+                    "│                     2024-07-20  50% ──────────────────────────────                               │",
+                    "│                                      30s / 1m | 13:37 -> 13:38                                   │",
+                ],
             ),
-            _ => format!(
-                "{time_now} / {len} | {} -> {until}",
-                last_reset.with_second(0).unwrap().format(TIME_FORMAT_SHORT)
+            (
+                &["-o"],
+                "2024-07-20 13:00:00",
+                [
+                    "│                     2024-07-20  62% ──────────────────────────────                               │",
+                    "│                                    37m 30s / 1h | 13:00 -> 14:00                                 │",
+                ],
             ),
-        };
+            (
+                &["-d"],
+                "2024-07-20 00:00:00",
+                [
+                    "│                     2024-07-20  57% ──────────────────────────────                               │",
+                    "│                                 13h 37m 30s / 1day | 00:00 -> 00:0                               │",
+                ],
+            ),
+            (
+                &["-c", "5m"],
+                "2024-07-20 13:35:00",
+                [
+                    "│                     2024-07-20  50% ──────────────────────────────                               │",
+                    "│                                    2m 30s / 5m | 13:35 -> 13:40                                  │",
+                ],
+            ),
+            (
+                &["-u", "1m"],
+                "2024-07-20 13:37:00",
+                [
+                    "│                     2024-07-20  50% ──────────────────────────────                               │",
+                    "│                                      30s / 1m | 13:37 -> 13:38                                   │",
+                ],
+            ),
+            (
+                &["-t"],
+                "2024-07-20 13:30:00",
+                [
+                    "│                     2024-07-20  0% ───────────────────────────────                               │",
+                    "│                                          13:30:00 + 7m 30s                                       │",
+                ],
+            ),
+            (
+                &["--pomodoro"],
+                "2024-07-20 13:30:00",
+                [
+                    "│                     2024-07-20  30% ──────────────────────────────                               │",
+                    "│                                 Work 1 | 7m 30s / 25m | 13:30 -> 1                               │",
+                ],
+            ),
+            (
+                &["--alarm", "14:00"],
+                "2024-07-20 13:30:00",
+                [
+                    "│                     2024-07-20  25% ──────────────────────────────                               │",
+                    "│                                    7m 30s / 30m | 13:30 -> 14:00                                 │",
+                ],
+            ),
+            (
+                &["--month"],
+                "2024-07-01 00:00:00",
+                [
+                    "│                     2024-07-20  63% ──────────────────────────────                               │",
+                    "│                                 19 days 13h / 1month -> 2024-08-01                               │",
+                ],
+            ),
+        ] {
+            let frame = snapshot(args, last_reset);
+            assert_eq!(
+                frame.lines().skip(1).take(2).collect::<Vec<_>>(),
+                expected,
+                "{args:?}"
+            );
+        }
+    }
 
-        Paragraph::new(text)
-            .alignment(Alignment::Center)
-            .block(
-                Block::default().padding(Padding::right(if inner_rect.width > 80 {
-                    timebarw_padding[0]
-                } else {
-                    timebarw_padding[1]
-                })),
-            )
-    })
+    #[test]
+    fn the_timezone_is_labeled_with_its_offset() {
+        let now = fixed_now();
+        assert_eq!(
+            zone_label(now.with_timezone(&chrono_tz::Asia::Tokyo)),
+            "JST UTC+09:00"
+        );
+        assert_eq!(
+            zone_label(now.with_timezone(&chrono_tz::Asia::Dubai)),
+            "UTC+04:00"
+        );
+        assert!(!parse(["some exec"]).shows_zone());
+        assert!(parse(["some exec", "--show-tz"]).shows_zone());
+    }
+
+    #[test]
+    fn the_compact_clock_is_a_single_line_above_the_timebar() {
+        let mut c = parse_at(["some exec", "--compact", "-u", "5m"], fixed_now());
+        let frame = c.render_once(80, 10).unwrap();
+        let weekday = c.localized(&c.in_timezone(fixed_now()), "%a ");
+        let row = |text: &str| frame.lines().position(|line| line.contains(text));
+        assert!(
+            row(&weekday).is_some_and(|clock| row("0% ─") > Some(clock)),
+            "{frame}"
+        );
+        assert!(!frame.contains("too small"), "{frame}");
+    }
+
+    #[test]
+    fn only_the_timebar_over_the_whole_width() {
+        let mut c = parse_at(["some exec", "--bar-only", "-u", "25m"], fixed_now());
+        let frame = c.render_once(60, 5).unwrap();
+        let date = c.fdate(c.in_timezone(fixed_now()));
+        assert!(!frame.contains(&date) && !frame.contains('█'), "{frame}");
+        assert!(frame.contains("/ 25m"), "{frame}");
+        let bar = frame.lines().find(|line| line.contains("0% ")).unwrap();
+        assert!(bar.matches('─').count() > 40, "{frame}");
+    }
+
+    #[test]
+    fn the_stopped_timer_with_milliseconds() {
+        // stopped on a whole second, but now is not
+        let now = fixed_now() + chrono::Duration::milliseconds(250);
+        let frame = parse_at(["some exec", "-t", "--stopped", "--ms"], now)
+            .render_once(100, 20)
+            .unwrap();
+        assert!(frame.contains("(stopped)"), "{frame}");
+    }
 }
@@ -0,0 +1,7 @@
+//! Make your terminal into a big clock
+//!
+//! Besides the `crock` binary, the clock can be rendered inside of other ratatui apps with the
+//! [`ClockWidget`](clock::ui::ClockWidget). A [`Clock`](clock::Clock) is built from its
+//! [`ClockConfig`](clock::ClockConfig), which can be parsed from command line arguments.
+
+pub mod clock;
@@ -2,7 +2,6 @@
 
 use std::io;
 
-use libpt::cli::clap::Parser;
 use libpt::log::{debug, Level, Logger};
 use ratatui::backend::CrosstermBackend;
 use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
@@ -12,9 +11,7 @@ use ratatui::crossterm::terminal::{
 };
 use ratatui::Terminal;
 
-use self::clock::Clock;
-
-mod clock;
+use crock::clock::{Clock, ClockConfig};
 
 fn main() -> anyhow::Result<()> {
     human_panic::setup_panic!(human_panic::Metadata::new(
@@ -25,12 +22,12 @@ fn main() -> anyhow::Result<()> {
     .homepage(env!("CARGO_PKG_HOMEPAGE")));
 
     // setup the cli
-    let clock = Clock::parse();
-    if clock.verbose.level() >= Level::DEBUG {
+    let mut clock = Clock::new(ClockConfig::from_config_and_args()?);
+    if clock.config.verbose.level() >= Level::DEBUG {
         let _logger = Logger::builder()
             .log_to_file(true)
             .log_dir("/tmp/crock/".into())
-            .set_level(clock.verbose.level())
+            .set_level(clock.config.verbose.level())
             .display_time(true)
             .build()?;
     } else {
@@ -38,8 +35,32 @@ fn main() -> anyhow::Result<()> {
     }
     debug!("set up logger");
 
-    #[cfg(debug_assertions)]
-    mock_tests();
+    #[cfg(feature = "sound")]
+    if clock.config.list_audio_devices {
+        for name in crock::clock::audio::output_devices()? {
+            println!("{name}");
+        }
+        return Ok(());
+    }
+
+    if clock.config.dry_run {
+        debug!("printing the schedule");
+        clock.setup()?;
+        clock.print_schedule();
+        return Ok(());
+    }
+
+    if clock.config.once {
+        debug!("printing a single frame");
+        let (width, height) = ratatui::crossterm::terminal::size().unwrap_or((80, 24));
+        print!("{}", clock.render_once(width, height)?);
+        return Ok(());
+    }
+
+    if clock.config.json {
+        debug!("printing json instead of taking over the terminal");
+        return crock::clock::json::run(clock);
+    }
 
     debug!("taking over terminal");
     // setup terminal
@@ -62,42 +83,10 @@ fn main() -> anyhow::Result<()> {
     )?;
     terminal.show_cursor()?;
 
+    if clock.config.print_laps {
+        clock.print_laps();
+    }
+
     debug!("done");
     result
 }
-
-#[cfg(debug_assertions)]
-#[allow(clippy::cast_precision_loss)]
-fn mock_tests() {
-    use chrono::{Local, Timelike};
-    use libpt::log::info;
-
-    use crate::clock::ui::Data;
-    info!("doing the mock tests");
-    {
-        let mut c = Clock::parse_from(["some exec", "-mvvv"]);
-        let now = Local::now();
-        c.last_reset = Some(now.with_second(0).unwrap());
-
-        assert_eq!(c.timebar_ratio(now.with_second(30).unwrap()), Some(0.5));
-        info!("30s=0.5");
-        assert_eq!(
-            c.timebar_ratio(now.with_second(59).unwrap()),
-            Some(0.9833333333333333)
-        );
-        info!("60s=1.0");
-        assert_eq!(c.timebar_ratio(now.with_second(0).unwrap()), Some(0.0));
-        info!("0s=0.0");
-    }
-    {
-        let mut data = Data::new(None);
-        let now = Local::now();
-        data.update(now, "date".to_owned(), "time".to_owned(), Some(0.1));
-        assert_eq!(data.timebar_ratio(), Some(0.1));
-        data.update(now, "date".to_owned(), "time".to_owned(), Some(0.2));
-        assert_eq!(data.timebar_ratio(), Some(0.2));
-        data.update(now, "date".to_owned(), "time".to_owned(), Some(0.3));
-        assert_eq!(data.timebar_ratio(), Some(0.3));
-    }
-    info!("finished the mock tests");
-}